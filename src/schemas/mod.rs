@@ -1,3 +1,4 @@
 pub mod struct_google_api;
 pub mod struct_migros;
-pub mod struct_enums;
\ No newline at end of file
+pub mod struct_enums;
+pub mod struct_platform;
\ No newline at end of file
@@ -1,4 +1,6 @@
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
+use crate::schemas::struct_migros::MigrosErrorMessage;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)] // Serde'nin JSON çıktısını düzleştirmesi için
@@ -14,15 +16,325 @@ pub enum ModifierGroupIdsEnumString {
     None(String), // örnek: "-" olarak tutulacak
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum RoofStep {
     KeyFiltrele,
     MenuGetir,
     Flagger,
     IstekAt,
+    Dogrula,
 }
 
+#[derive(Clone, Debug)]
 pub struct RoofStepResult {
     pub step: RoofStep,
+    pub store_id: Option<i64>, // KeyFiltrele şube seviyesinde çalıştığı için None
     pub result: Result<(), String>,
-}
\ No newline at end of file
+    pub item_count: usize,
+    pub elapsed: Duration,
+}
+
+/// Bir adım hata verdiğinde pipeline'ın davranışı.
+/// Her iki durumda da hata veren mağazanın kalan adımları atlanır.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoofFailurePolicy {
+    Continue, // diğer mağazalarla devam et
+    Stop,     // çalışmayı bu mağazadan sonra durdur
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RoofPipelinePolicy {
+    pub menu_getir: RoofFailurePolicy,
+    pub flagger: RoofFailurePolicy,
+    pub istek_at: RoofFailurePolicy,
+}
+
+impl Default for RoofPipelinePolicy {
+    fn default() -> Self {
+        Self {
+            menu_getir: RoofFailurePolicy::Continue,
+            flagger: RoofFailurePolicy::Continue,
+            istek_at: RoofFailurePolicy::Continue,
+        }
+    }
+}
+
+/// `DeliveryPlatform` üzerinden açılıp kapatılan menü öğesi; platformdan bağımsızdır.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlatformTarget {
+    Product(i64),
+    OptionItem(i64),
+}
+
+pub type MigrosChangeTarget = PlatformTarget;
+
+/// Platform isteğinin sonucu. Platforma özgü hata modelleri `Rejected` içinde yalnızca
+/// pipeline'ın uygulayacağı aksiyona ve okunabilir detaya indirgenir.
+#[derive(Clone, Debug)]
+pub enum PlatformOutcome {
+    Applied,
+    DryRun,           // istek hazırlandı ama gönderilmedi
+    AlreadyConfirmed, // daha önce onaylandığı için tekrar gönderilmedi
+    Rejected {
+        action: PlatformErrorAction,
+        detail: String,
+    },
+}
+
+/// Reddedilen bir istek karşısında pipeline'ın ne yapması gerektiği.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlatformErrorAction {
+    Retry,
+    Skip,
+    Alert,
+    Fail,
+}
+
+/// `MigrosActivateResponse` yanıtının sınıflandırılmış hali.
+#[derive(Clone, Debug)]
+pub enum MigrosStatusOutcome {
+    Success,
+    BusinessError(MigrosErrorMessage),
+    SystemError(MigrosErrorMessage),
+    ValidationError(Vec<String>),
+    DryRun,           // istek hazırlandı ama gönderilmedi
+    AlreadyConfirmed, // journal'da onaylı olduğu için tekrar gönderilmedi
+}
+
+impl MigrosStatusOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, MigrosStatusOutcome::Success)
+    }
+}
+
+/// Migros hata kodlarından türetilen tipli hata modeli.
+#[derive(Clone, Debug)]
+pub enum MigrosError {
+    KeyInvalid(MigrosErrorMessage),
+    ProductNotFound(MigrosErrorMessage),
+    Validation(Vec<String>),
+    RateLimited(MigrosErrorMessage),
+    System(MigrosErrorMessage),
+    Business(MigrosErrorMessage),
+}
+
+
+const MIGROS_KEY_ERROR_CODES: [&str; 7] = [
+    "401",
+    "403",
+    "UNAUTHORIZED",
+    "FORBIDDEN",
+    "INVALID_API_KEY",
+    "INVALID_RESTAURANT_KEY",
+    "DECRYPTION_FAILED",
+];
+
+const MIGROS_NOT_FOUND_ERROR_CODES: [&str; 4] = [
+    "404",
+    "PRODUCT_NOT_FOUND",
+    "OPTION_ITEM_NOT_FOUND",
+    "MENU_ITEM_NOT_FOUND",
+];
+
+const MIGROS_RATE_LIMIT_ERROR_CODES: [&str; 3] = ["429", "TOO_MANY_REQUESTS", "RATE_LIMIT_EXCEEDED"];
+
+const MIGROS_HTTP_ERROR_BODY_LIMIT: usize = 500;
+
+const MIGROS_VALIDATION_ERROR_CODES: [&str; 3] = ["400", "VALIDATION_ERROR", "INVALID_REQUEST"];
+
+impl MigrosError {
+    pub fn from_error_message(message: MigrosErrorMessage) -> Self {
+        let code = message.error_code.trim().to_uppercase();
+
+        if MIGROS_KEY_ERROR_CODES.contains(&code.as_str()) {
+            MigrosError::KeyInvalid(message)
+        } else if MIGROS_NOT_FOUND_ERROR_CODES.contains(&code.as_str())
+            || ((code.starts_with("PRODUCT_") || code.starts_with("OPTION_ITEM_")) && code.ends_with("_NOT_FOUND"))
+        {
+            MigrosError::ProductNotFound(message)
+        } else if MIGROS_RATE_LIMIT_ERROR_CODES.contains(&code.as_str()) {
+            MigrosError::RateLimited(message)
+        } else if MIGROS_VALIDATION_ERROR_CODES.contains(&code.as_str()) {
+            MigrosError::Validation(vec![format!("{}: {}", message.error_title, message.error_detail)])
+        } else if message.is_system_error {
+            MigrosError::System(message)
+        } else {
+            MigrosError::Business(message)
+        }
+    }
+
+    /// 2xx dışı HTTP yanıtından hata üretir. 401/403 anahtar, 429 limit, 400 doğrulama,
+    /// 5xx sistem hatasıdır; diğer 4xx kodları (404 dahil) iş kuralı hatası sayılır.
+    pub fn from_http_status(status: u16, body: &str) -> Self {
+        let message = MigrosErrorMessage {
+            error_code: format!("HTTP_{}", status),
+            error_title: format!("HTTP {}", status),
+            error_detail: body.chars().take(MIGROS_HTTP_ERROR_BODY_LIMIT).collect(),
+            is_system_error: status >= 500,
+        };
+
+        match status {
+            401 | 403 => MigrosError::KeyInvalid(message),
+            429 => MigrosError::RateLimited(message),
+            400 => MigrosError::Validation(vec![format!("{}: {}", message.error_title, message.error_detail)]),
+            500.. => MigrosError::System(message),
+            _ => MigrosError::Business(message),
+        }
+    }
+
+    pub fn error_code(&self) -> Option<&str> {
+        match self {
+            MigrosError::Validation(_) => None,
+            MigrosError::KeyInvalid(message)
+            | MigrosError::ProductNotFound(message)
+            | MigrosError::RateLimited(message)
+            | MigrosError::System(message)
+            | MigrosError::Business(message) => Some(&message.error_code),
+        }
+    }
+
+    /// `anyhow` hata zincirindeki `MigrosError`'ı bulur (ör. HTTP seviyesinde reddedilen istek).
+    pub fn find_in(error: &anyhow::Error) -> Option<&MigrosError> {
+        error.chain().find_map(|e| e.downcast_ref::<MigrosError>())
+    }
+
+    pub fn from_outcome(outcome: &MigrosStatusOutcome) -> Option<Self> {
+        match outcome {
+            MigrosStatusOutcome::Success
+            | MigrosStatusOutcome::DryRun
+            | MigrosStatusOutcome::AlreadyConfirmed => None,
+            MigrosStatusOutcome::BusinessError(message) | MigrosStatusOutcome::SystemError(message) => {
+                Some(Self::from_error_message(message.clone()))
+            }
+            MigrosStatusOutcome::ValidationError(messages) => Some(MigrosError::Validation(messages.clone())),
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, MigrosError::RateLimited(_) | MigrosError::System(_))
+    }
+
+    pub fn action(&self) -> PlatformErrorAction {
+        match self {
+            MigrosError::RateLimited(_) | MigrosError::System(_) => PlatformErrorAction::Retry,
+            MigrosError::ProductNotFound(_) => PlatformErrorAction::Skip,
+            MigrosError::KeyInvalid(_) => PlatformErrorAction::Alert,
+            MigrosError::Validation(_) | MigrosError::Business(_) => PlatformErrorAction::Fail,
+        }
+    }
+}
+
+impl std::fmt::Display for MigrosError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (category, message) = match self {
+            MigrosError::Validation(messages) => return write!(f, "Doğrulama hatası: {}", messages.join(" | ")),
+            MigrosError::KeyInvalid(message) => ("Anahtar/yetki hatası", message),
+            MigrosError::ProductNotFound(message) => ("Ürün bulunamadı", message),
+            MigrosError::RateLimited(message) => ("İstek limiti aşıldı", message),
+            MigrosError::System(message) => ("Sistem hatası", message),
+            MigrosError::Business(message) => ("İş kuralı hatası", message),
+        };
+
+        write!(
+            f,
+            "{} [{}] {} - {}",
+            category, message.error_code, message.error_title, message.error_detail
+        )
+    }
+}
+
+impl std::error::Error for MigrosError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrosJournalState {
+    Planned,
+    Sent,
+    Confirmed,
+    Failed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrosMenuDiffKind {
+    Added,
+    Removed,
+    PriceChanged,
+    StatusFlipped,
+}
+
+impl MigrosMenuDiffKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrosMenuDiffKind::Added => "added",
+            MigrosMenuDiffKind::Removed => "removed",
+            MigrosMenuDiffKind::PriceChanged => "price_changed",
+            MigrosMenuDiffKind::StatusFlipped => "status_flipped",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "added" => Some(MigrosMenuDiffKind::Added),
+            "removed" => Some(MigrosMenuDiffKind::Removed),
+            "price_changed" => Some(MigrosMenuDiffKind::PriceChanged),
+            "status_flipped" => Some(MigrosMenuDiffKind::StatusFlipped),
+            _ => None,
+        }
+    }
+}
+
+/// Durum değişikliğini kimin yaptığı: journal'da onaylı isteğimiz varsa biz, yoksa platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrosChangeSource {
+    Platform,
+    Us,
+}
+
+impl MigrosChangeSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrosChangeSource::Platform => "platform",
+            MigrosChangeSource::Us => "us",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "platform" => Some(MigrosChangeSource::Platform),
+            "us" => Some(MigrosChangeSource::Us),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrosFeedIssueKind {
+    InvalidRow,
+    NonPositivePrice,
+    EmptyName,
+    DuplicateProductId,
+    UnknownRestaurantKey,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrosMappingConflictKind {
+    UrunToManyProducts, // bir urun_id aynı restoranda birden fazla product_id'ye bağlı
+    ProductToManyUruns, // bir product_id birden fazla urun_id'ye bağlı
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrosRuleTarget {
+    Category(i64),
+    Product(i64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrosKeyIssueKind {
+    ParseError,
+    DuplicateStoreId,
+    StoreInMultipleBranches,
+    InvalidKeyLength,
+    BlankBranchName,
+    MenuIdNotFound,
+    ApiCheckFailed, // menu_id kontrolü not-found dışında bir hatayla sonuçlandı
+}
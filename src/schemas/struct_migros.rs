@@ -1,19 +1,16 @@
+use std::collections::HashMap;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use crate::utils::input::{string_to_f64, string_to_i64, string_to_option_i64};
-use crate::schemas::struct_enums::ModifierGroupIdsEnum;
+use crate::schemas::struct_enums::{
+    MigrosChangeSource, MigrosChangeTarget, MigrosFeedIssueKind, MigrosJournalState, MigrosKeyIssueKind,
+    MigrosMappingConflictKind, MigrosMenuDiffKind, MigrosRuleTarget, ModifierGroupIdsEnum,
+};
+use crate::schemas::struct_platform::{PlatformStatusChange, PlatformStore, PlatformVerificationMismatch};
 
-#[derive(Clone, Deserialize, Debug)]
-pub struct MigrosKeysRow {
-    pub chain_id: i64,
-    pub store_id: i64,
-    pub menu_id: i64,
-    pub brand_name: String,
-    pub brand_name_platform: String,
-    pub branch_name: String,
-    pub branch_brand_name: String,
-    pub branch_name_platform: String,
-    pub restaurant_key: String,
-}
+/// Migros anahtar tablosunun satırı; `restaurant_key` Migros API anahtarıdır.
+pub type MigrosKeysRow = PlatformStore;
 
 #[derive(Clone, Serialize, Debug)]
 pub struct MigrosPayloadMenuDetails {
@@ -29,7 +26,7 @@ pub struct MigrosPayloadOptions {
     pub menu_id: i64,
 }
 
-#[derive(Clone, Serialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct MigrosEncryptedPayload {
     pub value: String,
 }
@@ -72,6 +69,8 @@ pub struct MigrosResponseFoodMenuItemDetailsDTOs {
     pub product_id: i64,
     pub status: String,
     pub id: i64,
+    #[serde(default)]
+    pub price: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -119,6 +118,14 @@ pub struct MigrosResponseOptionItemDTO {
     pub status: String,
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrosOptionItemRow {
+    pub owner_product_id: i64,
+    pub option_header_id: i64,
+    pub option_item_id: i64,
+    pub status: String,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MigrosBranchJsonRow {
     pub restaurant_key: String,
@@ -148,6 +155,7 @@ pub struct MigrosPayloadStatusOptions {
     pub store_id: i64,
     #[serde(rename = "optionItemId")]
     pub option_item_id: i64,
+    pub status: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -159,6 +167,15 @@ pub struct MigrosPayloadStatusProduct {
     pub status: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrosPayloadPriceProduct {
+    #[serde(rename = "storeId")]
+    pub store_id: i64,
+    #[serde(rename = "productId")]
+    pub product_id: i64,
+    pub price: f64,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct MigrosActivateResponse {
     pub success: bool,
@@ -168,7 +185,7 @@ pub struct MigrosActivateResponse {
     pub validaton_error_messages: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MigrosErrorMessage {
     #[serde(rename = "errorCode")]
     pub error_code: String,
@@ -178,4 +195,231 @@ pub struct MigrosErrorMessage {
     pub error_detail: String,
     #[serde(rename = "isSystemError")]
     pub is_system_error: bool,
-}
\ No newline at end of file
+}
+
+#[derive(Debug)]
+pub struct MigrosStoreMenu {
+    pub menu: MigrosResponseMenuDetails,
+    pub options: MigrosResponseOptionsDetails,
+}
+
+pub type MigrosStatusChange = PlatformStatusChange;
+
+/// Flagger'ın hedef aldığı durum: iç ürün (`urun_id`) stok durumu, manuel override'lar ve
+/// zaman penceresi kararları. Override'lar `urun_id` eşleşmesinden önce gelir; zaman
+/// penceresi yalnızca kapatabilir (açık = pencere açık VE stokta).
+#[derive(Clone, Debug, Default)]
+pub struct MigrosDesiredState {
+    pub urun_status: HashMap<i64, bool>,
+    pub product_overrides: HashMap<i64, bool>,
+    pub option_overrides: HashMap<i64, bool>,
+    // (restaurant_id, product_id) → ürün için geçerli zaman penceresi kararı
+    pub product_windows: HashMap<(i64, i64), MigrosRuleDecision>,
+}
+
+/// Dry-run modunda gönderilmeden kaydedilen, şifrelenmiş haliyle birebir istek.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrosPlannedRequest {
+    pub store_id: i64,
+    pub path: String,
+    pub target: String,
+    pub target_id: i64,
+    pub value: String,
+    pub payload: Value,
+    pub encrypted: MigrosEncryptedPayload,
+}
+
+/// Run journal'ının (JSONL) bir satırı.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrosJournalEntry {
+    pub key: String,
+    pub state: MigrosJournalState,
+    pub store_id: i64,
+    pub target: String,
+    pub target_id: i64,
+    pub value: String,
+    pub detail: Option<String>,
+    pub at: String,
+}
+
+pub type MigrosVerificationMismatch = PlatformVerificationMismatch;
+
+/// Belirli bir günde çekilen menünün ürün/opsiyon bazlı kaydı.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrosMenuSnapshotRow {
+    pub snapshot_date: NaiveDate,
+    pub fetched_at: DateTime<Utc>, // menünün API'den çekildiği an; journal ile bu zamana göre eşleşir
+    pub restaurant_id: i64,
+    pub r#type: String,
+    pub product_id: i64,
+    pub category_id: i64,
+    pub status: bool,
+    pub price: Option<f64>,
+}
+
+/// İki snapshot arasındaki tek bir fark. Durum değişikliklerinde `changed_by` doludur.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrosMenuDiffEntry {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub restaurant_id: i64,
+    pub r#type: String,
+    pub product_id: i64,
+    pub kind: MigrosMenuDiffKind,
+    pub old_status: Option<bool>,
+    pub new_status: Option<bool>,
+    pub old_price: Option<f64>,
+    pub new_price: Option<f64>,
+    pub changed_by: Option<MigrosChangeSource>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrosFeedIssue {
+    pub row_index: usize, // feed'deki `data` dizisinde 0 tabanlı sıra
+    pub restaurant_id: Option<i64>,
+    pub product_id: Option<i64>,
+    pub kind: MigrosFeedIssueKind,
+    pub detail: String,
+}
+
+/// Doğrulamadan geçen satırlar ve bulunan tüm sorunlar.
+#[derive(Debug, Default, Serialize)]
+pub struct MigrosBranchFeedReport {
+    pub rows: Vec<MigrosBranchJsonRow>,
+    pub issues: Vec<MigrosFeedIssue>,
+}
+
+/// İç ürün kataloğundaki bir ürün.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrosInternalProduct {
+    pub urun_id: i64,
+    pub name: String,
+    pub category_name: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrosMappingCandidate {
+    pub urun_id: i64,
+    pub name: String,
+    pub category_name: String,
+    pub score: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrosUnmappedProduct {
+    pub restaurant_id: i64,
+    pub product_id: i64,
+    pub product_name: String,
+    pub category_name: String,
+    pub candidates: Vec<MigrosMappingCandidate>,
+}
+
+/// Platformda bulunmayan iç ürün. `restaurant_id`/`product_id` doluysa eşleşme var
+/// ancak ürün canlı menüde yok; boşsa hiçbir restoranda eşleşmesi yok.
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrosMissingProduct {
+    pub urun_id: i64,
+    pub name: String,
+    pub restaurant_id: Option<i64>,
+    pub product_id: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrosMappingConflict {
+    pub restaurant_id: i64,
+    pub kind: MigrosMappingConflictKind,
+    pub urun_ids: Vec<i64>,
+    pub product_ids: Vec<i64>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MigrosMappingReport {
+    pub unmapped_platform: Vec<MigrosUnmappedProduct>,
+    pub missing_on_platform: Vec<MigrosMissingProduct>,
+    pub conflicts: Vec<MigrosMappingConflict>,
+}
+
+/// "Kahvaltı kategorisi hafta içi 07:00-11:30 arası açık" gibi bir zaman penceresi kuralı.
+/// `end` < `start` ise pencere gece yarısını aşar (ör. 22:00-02:00).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrosAvailabilityRule {
+    pub name: String,
+    pub target: MigrosRuleTarget,
+    pub weekdays: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+fn default_utc_offset_minutes() -> i32 {
+    180 // Europe/Istanbul, 2016'dan beri yaz saati uygulaması olmadan UTC+3
+}
+
+fn default_holiday_as() -> Option<Weekday> {
+    Some(Weekday::Sun)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrosAvailabilityRuleSet {
+    #[serde(default = "default_utc_offset_minutes")]
+    pub utc_offset_minutes: i32,
+    #[serde(default)]
+    pub holidays: Vec<NaiveDate>,
+    /// Tatil günleri hangi gün gibi değerlendirilecek; `None` ise normal gün gibi.
+    #[serde(default = "default_holiday_as")]
+    pub holiday_as: Option<Weekday>,
+    pub rules: Vec<MigrosAvailabilityRule>,
+}
+
+/// Kurallardan çıkan, belirli bir an için ürün ya da kategori penceresinin açık/kapalı
+/// durumu.
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrosRuleDecision {
+    pub restaurant_id: i64,
+    pub target: MigrosRuleTarget,
+    pub status: bool,
+    pub rule_names: Vec<String>,
+}
+
+/// Stok-out kuralının kapattığı bir ürün/opsiyon. Yeniden stoğa girişte yalnızca bu
+/// kayıtlar açılır.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrosStockOutLedgerEntry {
+    pub branch_name: String,
+    pub urun_id: i64,
+    pub store_id: i64,
+    pub target: MigrosChangeTarget,
+    pub disabled_at: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MigrosStockOutLedger {
+    pub entries: Vec<MigrosStockOutLedgerEntry>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MigrosStockOutResult {
+    pub applied: Vec<MigrosStatusChange>,
+    pub already_confirmed: Vec<MigrosStatusChange>, // önceki çalışmada onaylandığı için gönderilmeyenler
+    pub failed: Vec<MigrosStatusChange>,
+    pub fetch_errors: Vec<MigrosStoreFetchError>, // menüsü çekilemediği için atlanan mağazalar
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrosStoreFetchError {
+    pub store_id: i64,
+    pub error: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrosKeyIssue {
+    pub sheet_row: usize, // Google Sheet'teki satır numarası (başlık satırı 1)
+    pub store_id: Option<i64>,
+    pub kind: MigrosKeyIssueKind,
+    pub detail: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MigrosKeysValidationReport {
+    pub checked_rows: usize,
+    pub issues: Vec<MigrosKeyIssue>,
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use crate::schemas::struct_enums::PlatformTarget;
+
+/// Platformdan bağımsız mağaza kaydı: kimlik, zincir/menü bilgisi, isimler ve API anahtarı.
+/// Migros tarafında `MigrosKeysRow` adıyla kullanılır.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlatformStore {
+    pub chain_id: i64,
+    pub store_id: i64,
+    pub menu_id: i64,
+    pub brand_name: String,
+    pub brand_name_platform: String,
+    pub branch_name: String,
+    pub branch_brand_name: String,
+    pub branch_name_platform: String,
+    pub restaurant_key: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlatformStatusChange {
+    pub store_id: i64,
+    pub target: PlatformTarget,
+    pub status: bool,
+    pub reason: String,
+}
+
+/// Doğrulama sırasında canlı menüde istenen durumda görünmeyen değişiklik.
+#[derive(Clone, Debug, Serialize)]
+pub struct PlatformVerificationMismatch {
+    pub store_id: i64,
+    pub target: PlatformTarget,
+    pub expected: bool,
+    pub actual: Option<bool>, // None → canlı menüde bulunamadı
+}
+
+/// Feed'den gelen, platforma gönderilecek ürün fiyatı.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlatformFeedPrice {
+    pub store_id: i64,
+    pub product_id: i64,
+    pub price: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlatformPriceChange {
+    pub store_id: i64,
+    pub product_id: i64,
+    pub old_price: Option<f64>, // None → canlı menüde fiyat bilgisi yok
+    pub price: f64,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PlatformPriceMismatch {
+    pub store_id: i64,
+    pub product_id: i64,
+    pub expected: f64,
+    pub actual: Option<f64>, // None → ürün ya da fiyat bilgisi canlı menüde yok
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::future::Future;
+
+use anyhow::Result;
+use log::{info, warn};
+
+use crate::schemas::struct_enums::{PlatformOutcome, PlatformTarget};
+use crate::schemas::struct_platform::{
+    PlatformStatusChange, PlatformStore, PlatformVerificationMismatch,
+};
+use crate::utils::encrypt::PayloadCipherFactory;
+
+/// Yemek siparişi pazaryerleri için ortak arayüz. `RoofPipeline`, fiyat senkronu ve
+/// doğrulama adımları bu trait üzerinden çalışır; yeni bir platform eklemek için yalnızca
+/// bu trait'in uygulanması yeterlidir. İlk uygulama `MigrosClient`'tır.
+///
+/// Arayüz platformdan bağımsız tiplerle (`PlatformStore`, `PlatformStatusChange`,
+/// `PlatformOutcome`) çalışır; platforma özgü menü yanıtları ilişkili tipler olarak,
+/// platforma özgü hata modelleri ise `PlatformOutcome::Rejected` aksiyonuna indirgenerek
+/// uygulama içinde kalır.
+pub trait DeliveryPlatform: Clone + Send + Sync {
+    type Menu: Send + Sync;
+    type Options: Send + Sync;
+    /// Flagger'a verilen, menü ve opsiyonları bir arada tutan tip.
+    type StoreMenu: Send + Sync;
+
+    fn name(&self) -> &'static str;
+
+    fn is_dry_run(&self) -> bool;
+
+    /// Payload şifreleme şemasını belirler; her platform kendi varsayılanıyla başlar.
+    fn bind_cipher(self, factory: PayloadCipherFactory) -> Self;
+
+    /// İstemciyi verilen mağazaların anahtarları ve zincir bilgisiyle hazırlar.
+    fn bind_keys(self, stores: &[PlatformStore]) -> Self;
+
+    fn fetch_menu(
+        &self,
+        store: &PlatformStore,
+    ) -> impl Future<Output = Result<Self::Menu>> + Send;
+
+    fn fetch_options(
+        &self,
+        store: &PlatformStore,
+    ) -> impl Future<Output = Result<Self::Options>> + Send;
+
+    // `set_*` metotlarında platformun reddettiği istekler `Ok(PlatformOutcome::Rejected)`
+    // döner; `Err` yalnızca sınıflandırılamayan (ör. ağ) hatalar içindir.
+
+    fn set_product_status(
+        &self,
+        store_id: i64,
+        product_id: i64,
+        status: bool,
+    ) -> impl Future<Output = Result<PlatformOutcome>> + Send;
+
+    fn set_option_status(
+        &self,
+        store_id: i64,
+        option_item_id: i64,
+        status: bool,
+    ) -> impl Future<Output = Result<PlatformOutcome>> + Send;
+
+    fn set_price(
+        &self,
+        store_id: i64,
+        product_id: i64,
+        price: f64,
+    ) -> impl Future<Output = Result<PlatformOutcome>> + Send;
+
+    fn store_menu(menu: Self::Menu, options: Self::Options) -> Self::StoreMenu;
+
+    /// Ürün ve opsiyonların canlı açık/kapalı durumları.
+    fn live_statuses(store_menu: &Self::StoreMenu) -> HashMap<PlatformTarget, bool>;
+
+    /// Ürünlerin canlı fiyatları; fiyatı olmayan ürünler `None` ile yer alır.
+    fn live_prices(menu: &Self::Menu) -> HashMap<i64, Option<f64>>;
+
+    /// Ürün sayısı; pipeline raporundaki adet alanı için kullanılır.
+    fn product_count(store_menu: &Self::StoreMenu) -> usize;
+
+    fn fetch_store_menu(
+        &self,
+        store: &PlatformStore,
+    ) -> impl Future<Output = Result<Self::StoreMenu>> + Send {
+        async move {
+            let menu = self.fetch_menu(store).await?;
+            let options = self.fetch_options(store).await?;
+            Ok(Self::store_menu(menu, options))
+        }
+    }
+
+    fn set_status(
+        &self,
+        change: &PlatformStatusChange,
+    ) -> impl Future<Output = Result<PlatformOutcome>> + Send {
+        async move {
+            match change.target {
+                PlatformTarget::Product(product_id) => {
+                    self.set_product_status(change.store_id, product_id, change.status)
+                        .await
+                }
+                PlatformTarget::OptionItem(option_item_id) => {
+                    self.set_option_status(change.store_id, option_item_id, change.status)
+                        .await
+                }
+            }
+        }
+    }
+}
+
+/// Mağazanın canlı menüsünü yeniden çekip `changes` içindeki hedef durumlarla karşılaştırır;
+/// uygulanmamış görünen değişiklikleri döner.
+pub async fn verify_platform_status_changes<P: DeliveryPlatform>(
+    platform: &P,
+    store: &PlatformStore,
+    changes: &[PlatformStatusChange],
+) -> Result<Vec<PlatformVerificationMismatch>> {
+    let changes: Vec<&PlatformStatusChange> = changes
+        .iter()
+        .filter(|change| change.store_id == store.store_id)
+        .collect();
+
+    if changes.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let live = P::live_statuses(&platform.fetch_store_menu(store).await?);
+
+    let mismatches: Vec<PlatformVerificationMismatch> = changes
+        .iter()
+        .filter_map(|change| {
+            let actual = live.get(&change.target).copied();
+            (actual != Some(change.status)).then_some(PlatformVerificationMismatch {
+                store_id: change.store_id,
+                target: change.target,
+                expected: change.status,
+                actual,
+            })
+        })
+        .collect();
+
+    for mismatch in &mismatches {
+        warn!(
+            "⚠️ Değişiklik canlı menüde görünmüyor → {} store_id: {}, {:?}, beklenen: {}, canlı: {:?}",
+            platform.name(),
+            mismatch.store_id,
+            mismatch.target,
+            mismatch.expected,
+            mismatch.actual
+        );
+    }
+
+    info!(
+        "✅ Doğrulama tamamlandı → {} store_id: {}, kontrol edilen: {}, uyumsuz: {}",
+        platform.name(),
+        store.store_id,
+        changes.len(),
+        mismatches.len()
+    );
+
+    Ok(mismatches)
+}
@@ -63,7 +63,7 @@ pub async fn get_spreadsheet_by_name(
 
     let file_id = json_response
         .files
-        .get(0)
+        .first()
         .ok_or_else(|| anyhow::anyhow!("❌ İstenilen dosya bulunamadı → '{}'", spreadsheet_name))?
         .id
         .clone();
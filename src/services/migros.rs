@@ -1,13 +1,13 @@
 use std::collections::{HashMap, HashSet};
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::{Context, Result, anyhow, bail};
-use futures::stream::{FuturesUnordered, StreamExt};
 use log::{error, info, warn};
-use serde_json::{Value, json};
+use serde_json::json;
 use tokio::{
     fs,
     fs::File,
@@ -17,11 +17,61 @@ use tokio::{
 };
 
 use crate::schemas::struct_google_api::GoogleSheetResponse;
-use crate::services::google_api_sheet::get_sheet_values;
-use crate::schemas::struct_migros::{MigrosKeysRow};
+use crate::services::delivery_platform::DeliveryPlatform;
+use crate::services::platform_price::price_str;
+use crate::services::store_filter::{StoreFilter, TextPattern};
+use crate::services::google_api_sheet::{append_sheet_values, clear_sheet_range, get_sheet_values};
+use crate::schemas::struct_enums::{
+    MigrosChangeTarget, MigrosError, MigrosJournalState, MigrosKeyIssueKind, MigrosStatusOutcome,
+    PlatformOutcome, PlatformTarget,
+};
+use crate::schemas::struct_migros::{
+    MigrosActivateResponse, MigrosEncryptedPayload, MigrosErrorMessage, MigrosJournalEntry,
+    MigrosKeyIssue, MigrosKeysRow, MigrosKeysValidationReport, MigrosOptionItemRow, MigrosPayloadMenuDetails, MigrosPayloadOptions, MigrosPayloadStatusOptions,
+    MigrosPayloadPriceProduct, MigrosPayloadStatusProduct, MigrosPlannedRequest, MigrosResponseMenuDetails,
+    MigrosResponseOptionsDetails, MigrosStatusChange, MigrosStoreMenu,
+};
+use crate::utils::concurrency::fan_out;
+use crate::utils::encrypt::{MigrosKey, PayloadCipherFactory};
+use crate::utils::http::http_request_post;
 
 const KEYS_SHEET_ID: &str = "1accka-4YjSUwd27UNgG3xcpWy4Inz0W4E_NVNfT3-xk";
 const MIGROS_KEYS_RANGE: &str = "MİGROS!A2:I";
+const MIGROS_KEYS_FIRST_ROW: usize = 2;
+const MIGROS_KEYS_VALIDATION_RANGE: &str = "validation!A1:E";
+
+const MIGROS_API_BASE_URL: &str = "https://restaurant-integration.migrosyemek.com/api";
+pub const MIGROS_MENU_DETAILS_PATH: &str = "/v1/store/menu-details";
+pub const MIGROS_OPTIONS_DETAILS_PATH: &str = "/v1/menu/options";
+pub const MIGROS_PRODUCT_STATUS_PATH: &str = "/v1/product/status";
+pub const MIGROS_OPTION_STATUS_PATH: &str = "/v1/option-item/status";
+pub const MIGROS_PRODUCT_PRICE_PATH: &str = "/v1/product/price";
+// Opsiyon servisinin menü bulunamadığında döndüğü kodlar; diğer hatalar menu_id
+// kontrolünde `ApiCheckFailed` sayılır
+const MIGROS_MENU_NOT_FOUND_ERROR_CODES: [&str; 3] = ["HTTP_404", "MENU_NOT_FOUND", "MENU_ID_NOT_FOUND"];
+const MIGROS_DEFAULT_MAX_RETRIES: u32 = 3;
+const MIGROS_RETRY_BASE_DELAY_MS: u64 = 500;
+const MIGROS_RETRY_MAX_DELAY_MS: u64 = 30_000;
+const MIGROS_DEFAULT_STORE_INTERVAL_MS: u64 = 250;
+const MIGROS_DEFAULT_CHAIN_INTERVAL_MS: u64 = 50;
+
+pub const MIGROS_STATUS_ACTIVE: &str = "ACTIVE";
+pub const MIGROS_STATUS_PASSIVE: &str = "PASSIVE";
+
+pub const MIGROS_TYPE_PRODUCT: &str = "product";
+pub const MIGROS_TYPE_OPTION: &str = "option";
+
+pub fn migros_status_str(status: bool) -> &'static str {
+    if status {
+        MIGROS_STATUS_ACTIVE
+    } else {
+        MIGROS_STATUS_PASSIVE
+    }
+}
+
+pub fn migros_status_bool(status: &str) -> bool {
+    status.eq_ignore_ascii_case(MIGROS_STATUS_ACTIVE)
+}
 
 pub fn parse_migros_keys(row: &[String]) -> Result<MigrosKeysRow> {
     if row.len() < 9 {
@@ -50,37 +100,27 @@ pub fn parse_migros_keys(row: &[String]) -> Result<MigrosKeysRow> {
     })
 }
 
+/// Şube adı Türkçe büyük/küçük harf ve boşluk farklarına duyarsız karşılaştırılır;
+/// `*`/`?` içeren adlar glob olarak yorumlanır. Eşleşme yoksa hata döner.
 pub async fn filter_migros_keys_by_branch(
     auth_token: &str,
     branch_name: &str,
 ) -> Result<Vec<MigrosKeysRow>> {
-    let sheet_values = get_sheet_values(KEYS_SHEET_ID, MIGROS_KEYS_RANGE, auth_token)
-        .await
-        .with_context(|| "❌ Migros key sheet verileri alınamadı")?;
-
-    let mut parsed_keys = vec![];
-    let mut skipped_count = 0;
-
-    for (i, row) in sheet_values.values.iter().enumerate() {
-        match parse_migros_keys(row) {
-            Ok(parsed) => parsed_keys.push(parsed),
-            Err(e) => {
-                skipped_count += 1;
-                warn!("⚠️ Satır {} parse edilemedi → Hata: {:?}", i + 1, e);
-            }
-        }
-    }
+    let filter = StoreFilter::new().with_branch(TextPattern::parse(branch_name)?);
+    filter_migros_keys(auth_token, &filter).await
+}
 
-    let filtered_keys: Vec<MigrosKeysRow> = parsed_keys
-        .into_iter()
-        .filter(|key| key.branch_name == branch_name)
-        .collect();
+pub async fn filter_migros_keys(
+    auth_token: &str,
+    filter: &StoreFilter,
+) -> Result<Vec<MigrosKeysRow>> {
+    let keys = get_migros_keys(auth_token).await?;
+    let filtered_keys = filter.apply(&keys)?;
 
     info!(
-        "✅ '{}' şubesi için {} anahtar bulundu. Atlanan satır sayısı: {}",
-        branch_name,
-        filtered_keys.len(),
-        skipped_count
+        "✅ Filtre ({}) için {} anahtar bulundu.",
+        filter,
+        filtered_keys.len()
     );
 
     Ok(filtered_keys)
@@ -113,4 +153,1046 @@ pub async fn get_migros_keys(
     );
 
     Ok(parsed_keys)
-}
\ No newline at end of file
+}
+
+/// Anahtar sheet'indeki tüm satırları kontrol eder ve sorunları sheet satır numarasıyla
+/// raporlar: çözümlenemeyen satırlar, tekrar eden `store_id`, birden fazla şubeye bağlı
+/// mağaza, geçerli bir AES-256 anahtarı olmayan `restaurant_key` ve boş şube adları.
+pub fn validate_migros_keys(sheet: &GoogleSheetResponse) -> MigrosKeysValidationReport {
+    let mut report = MigrosKeysValidationReport {
+        checked_rows: sheet.values.len(),
+        issues: vec![],
+    };
+    let mut first_seen: HashMap<i64, (usize, String)> = HashMap::new();
+
+    for (i, row) in sheet.values.iter().enumerate() {
+        let sheet_row = i + MIGROS_KEYS_FIRST_ROW;
+        let key = match parse_migros_keys(row) {
+            Ok(key) => key,
+            Err(e) => {
+                report.issues.push(MigrosKeyIssue {
+                    sheet_row,
+                    store_id: None,
+                    kind: MigrosKeyIssueKind::ParseError,
+                    detail: format!("{:#}", e),
+                });
+                continue;
+            }
+        };
+
+        let mut issue = |kind: MigrosKeyIssueKind, detail: String| {
+            report.issues.push(MigrosKeyIssue {
+                sheet_row,
+                store_id: Some(key.store_id),
+                kind,
+                detail,
+            })
+        };
+
+        if key.branch_name.trim().is_empty() {
+            issue(MigrosKeyIssueKind::BlankBranchName, "branch_name boş".to_string());
+        }
+
+        if let Err(e) = MigrosKey::parse(&key.restaurant_key) {
+            issue(MigrosKeyIssueKind::InvalidKeyLength, format!("{:#}", e));
+        }
+
+        match first_seen.get(&key.store_id) {
+            Some((first_row, first_branch)) => {
+                issue(
+                    MigrosKeyIssueKind::DuplicateStoreId,
+                    format!("store_id daha önce satır {} içinde var", first_row),
+                );
+                if first_branch.trim() != key.branch_name.trim() {
+                    issue(
+                        MigrosKeyIssueKind::StoreInMultipleBranches,
+                        format!(
+                            "store_id satır {} içinde '{}' şubesine, burada '{}' şubesine bağlı",
+                            first_row, first_branch, key.branch_name
+                        ),
+                    );
+                }
+            }
+            None => {
+                first_seen.insert(key.store_id, (sheet_row, key.branch_name.clone()));
+            }
+        }
+    }
+
+    report
+}
+
+/// Her satırın `menu_id` değerini opsiyon servisine sorarak API'de olmayan menüleri
+/// rapora ekler. Yalnızca API'nin açıkça "bulunamadı" dediği menüler `MenuIdNotFound`,
+/// diğer hatalar (anahtar, ağ, bozuk yanıt) `ApiCheckFailed` olarak raporlanır; boş
+/// opsiyon listesi sorun sayılmaz.
+pub async fn check_migros_menu_ids(
+    client: &MigrosClient,
+    sheet: &GoogleSheetResponse,
+    report: &mut MigrosKeysValidationReport,
+    max_concurrency: usize,
+) {
+    let rows: Vec<(usize, MigrosKeysRow)> = sheet
+        .values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, row)| parse_migros_keys(row).ok().map(|key| (i + MIGROS_KEYS_FIRST_ROW, key)))
+        .collect();
+
+    let issues = fan_out(rows, max_concurrency, "menu_id kontrolü", |(sheet_row, key)| async move {
+        let e = client.get_options_details(&key).await.err()?;
+        let not_found = MigrosError::find_in(&e)
+            .and_then(MigrosError::error_code)
+            .is_some_and(|code| MIGROS_MENU_NOT_FOUND_ERROR_CODES.contains(&code.trim().to_uppercase().as_str()));
+
+        Some(MigrosKeyIssue {
+            sheet_row,
+            store_id: Some(key.store_id),
+            kind: if not_found {
+                MigrosKeyIssueKind::MenuIdNotFound
+            } else {
+                MigrosKeyIssueKind::ApiCheckFailed
+            },
+            detail: format!("menu_id {} → {:#}", key.menu_id, e),
+        })
+    })
+    .await;
+
+    report.issues.extend(issues.into_iter().flatten());
+    report.issues.sort_by_key(|issue| issue.sheet_row);
+}
+
+/// Anahtar sheet'ini çekip doğrular; `client` verilirse `menu_id` kontrolü de yapılır.
+pub async fn fetch_and_validate_migros_keys(
+    auth_token: &str,
+    client: Option<&MigrosClient>,
+) -> Result<MigrosKeysValidationReport> {
+    let sheet = get_sheet_values(KEYS_SHEET_ID, MIGROS_KEYS_RANGE, auth_token)
+        .await
+        .with_context(|| "❌ Migros key sheet verileri alınamadı")?;
+
+    let mut report = validate_migros_keys(&sheet);
+    if let Some(client) = client {
+        check_migros_menu_ids(client, &sheet, &mut report, 4).await;
+    }
+
+    info!(
+        "✅ Migros anahtar doğrulaması → kontrol edilen satır: {}, sorun: {}",
+        report.checked_rows,
+        report.issues.len()
+    );
+
+    Ok(report)
+}
+
+pub fn migros_keys_validation_sheet_values(report: &MigrosKeysValidationReport) -> Vec<Vec<String>> {
+    let mut values = vec![vec![
+        "sheet_row".to_string(),
+        "store_id".to_string(),
+        "kind".to_string(),
+        "detail".to_string(),
+        "checked_at".to_string(),
+    ]];
+    let checked_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    values.extend(report.issues.iter().map(|issue| {
+        vec![
+            issue.sheet_row.to_string(),
+            issue.store_id.map_or(String::new(), |id| id.to_string()),
+            format!("{:?}", issue.kind),
+            issue.detail.clone(),
+            checked_at.clone(),
+        ]
+    }));
+
+    values
+}
+
+/// Raporu anahtar sheet'indeki "validation" sekmesine yazar (önceki içerik temizlenir).
+pub async fn write_migros_keys_validation(report: &MigrosKeysValidationReport, auth_token: &str) -> Result<()> {
+    clear_sheet_range(KEYS_SHEET_ID, MIGROS_KEYS_VALIDATION_RANGE, auth_token).await?;
+    append_sheet_values(
+        KEYS_SHEET_ID,
+        MIGROS_KEYS_VALIDATION_RANGE,
+        migros_keys_validation_sheet_values(report),
+        auth_token,
+    )
+    .await
+}
+
+/// Migros restoran entegrasyon API'si için istemci.
+///
+/// Her istek, ilgili mağazanın `restaurant_key` değeri ile şifrelenip
+/// `MigrosEncryptedPayload` içinde gönderilir.
+#[derive(Clone, Debug)]
+pub struct MigrosClient {
+    base_url: String,
+    // Geçersiz anahtarların çözümleme hatası saklanır ve o mağazanın isteklerinde döner
+    store_keys: HashMap<i64, Result<MigrosKey, String>>,
+    store_chains: HashMap<i64, i64>,
+    cipher_factory: PayloadCipherFactory,
+    max_retries: u32,
+    rate_limiter: Option<MigrosRateLimiter>,
+    dry_run: bool,
+    plan: Arc<Mutex<Vec<MigrosPlannedRequest>>>,
+    journal: Option<Arc<MigrosJournal>>,
+}
+
+impl Default for MigrosClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MigrosClient {
+    pub fn new() -> Self {
+        Self {
+            base_url: MIGROS_API_BASE_URL.to_string(),
+            store_keys: HashMap::new(),
+            store_chains: HashMap::new(),
+            cipher_factory: PayloadCipherFactory::default(),
+            max_retries: MIGROS_DEFAULT_MAX_RETRIES,
+            rate_limiter: None,
+            dry_run: false,
+            plan: Arc::new(Mutex::new(vec![])),
+            journal: None,
+        }
+    }
+
+    /// Durum isteklerini journal'a yazar; journal resume modunda açıldıysa daha önce
+    /// onaylanmış istekler tekrar gönderilmez.
+    pub fn with_journal(mut self, journal: Arc<MigrosJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Dry-run açıkken durum değiştiren istekler hazırlanıp şifrelenir, plana yazılır
+    /// ancak gönderilmez. Menü/opsiyon okuma istekleri normal şekilde çalışır.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Dry-run sırasında biriken istekler.
+    pub async fn planned_requests(&self) -> Vec<MigrosPlannedRequest> {
+        self.plan.lock().await.clone()
+    }
+
+    /// Biriken planı JSON dosyası olarak yazar.
+    pub async fn write_plan(&self, path: &Path) -> Result<()> {
+        let plan = self.planned_requests().await;
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("❌ Plan klasörü oluşturulamadı → {:?}", parent))?;
+        }
+
+        let content = serde_json::to_string_pretty(&json!({
+            "generated_at": chrono::Local::now().to_rfc3339(),
+            "request_count": plan.len(),
+            "requests": plan,
+        }))
+        .context("❌ Plan JSON'a çevrilemedi")?;
+
+        let mut file = File::create(path)
+            .await
+            .with_context(|| format!("❌ Plan dosyası oluşturulamadı → {:?}", path))?;
+        file.write_all(content.as_bytes())
+            .await
+            .with_context(|| format!("❌ Plan dosyası yazılamadı → {:?}", path))?;
+
+        info!("✅ Dry-run planı yazıldı → {:?}, istek sayısı: {}", path, plan.len());
+        Ok(())
+    }
+
+    /// Durum güncellemelerinde `store_id` üzerinden şifreleme anahtarını bulabilmek için
+    /// mağaza anahtarlarını istemciye kaydeder.
+    /// Geçersiz anahtarlar loglanır ve çözümleme hatası saklanır; o mağazaya yapılan
+    /// istekler bu hatayla döner.
+    pub fn with_keys(mut self, keys: &[MigrosKeysRow]) -> Self {
+        for key in keys {
+            let restaurant_key = MigrosKey::parse_with(&key.restaurant_key, &self.cipher_factory)
+                .map_err(|e| {
+                    error!("❌ restaurant_key geçersiz → store_id: {}, {:#}", key.store_id, e);
+                    format!("{:#}", e)
+                });
+            self.store_keys.insert(key.store_id, restaurant_key);
+            self.store_chains.insert(key.store_id, key.chain_id);
+        }
+        self
+    }
+
+    /// Payload şifreleme şemasını değiştirir (varsayılan AES-256-ECB). Daha önce
+    /// kaydedilmiş mağaza anahtarları yeni şemayla yeniden kurulur.
+    pub fn with_cipher(mut self, factory: PayloadCipherFactory) -> Self {
+        self.cipher_factory = factory;
+        for (store_id, restaurant_key) in self.store_keys.iter_mut() {
+            if let Ok(key) = restaurant_key {
+                *restaurant_key = MigrosKey::parse_with(key.as_str(), &self.cipher_factory).map_err(|e| {
+                    error!("❌ restaurant_key geçersiz → store_id: {}, {:#}", store_id, e);
+                    format!("{:#}", e)
+                });
+            }
+        }
+        self
+    }
+
+    /// İstekleri mağaza ve zincir bazında aralıklandırır. Limitler istemcinin tüm
+    /// kopyaları arasında paylaşılır.
+    pub fn with_rate_limits(mut self, limits: MigrosRateLimits) -> Self {
+        self.rate_limiter = Some(MigrosRateLimiter::new(limits));
+        self
+    }
+
+    async fn throttle(&self, store_id: i64) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(self.store_chains.get(&store_id).copied(), store_id).await;
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sistem hatası ve istek limiti gibi tekrar denenebilir hatalarda yapılacak
+    /// en fazla yeniden deneme sayısı.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn restaurant_key(&self, store_id: i64) -> Result<&MigrosKey> {
+        match self.store_keys.get(&store_id) {
+            Some(Ok(key)) => Ok(key),
+            Some(Err(e)) => Err(anyhow!("❌ restaurant_key geçersiz → store_id: {}, {}", store_id, e)),
+            None => Err(anyhow!("❌ store_id için restaurant_key bulunamadı → {}", store_id)),
+        }
+    }
+
+    /// Okuma istekleri için anahtar; `with_keys` ile verilmiş aynı anahtar varsa hazır
+    /// şifreleyici kullanılır.
+    fn row_key(&self, key_row: &MigrosKeysRow) -> Result<MigrosKey> {
+        match self.store_keys.get(&key_row.store_id) {
+            Some(Ok(key)) if key.as_str() == key_row.restaurant_key.trim() => Ok(key.clone()),
+            _ => MigrosKey::parse_with(&key_row.restaurant_key, &self.cipher_factory)
+                .with_context(|| format!("❌ restaurant_key geçersiz → store_id: {}", key_row.store_id)),
+        }
+    }
+
+    fn encrypt_payload<T: serde::Serialize>(
+        &self,
+        path: &str,
+        payload: &T,
+        restaurant_key: &MigrosKey,
+    ) -> Result<MigrosEncryptedPayload> {
+        Ok(MigrosEncryptedPayload {
+            value: restaurant_key
+                .encrypt(payload)
+                .with_context(|| format!("❌ Migros payload şifrelenemedi → {}", path))?,
+        })
+    }
+
+    async fn post_encrypted<T, R>(&self, path: &str, payload: &T, restaurant_key: &MigrosKey) -> Result<R>
+    where
+        T: serde::Serialize,
+        R: serde::de::DeserializeOwned,
+    {
+        let url = format!("{}{}", self.base_url, path);
+        let encrypted = self.encrypt_payload(path, payload, restaurant_key)?;
+
+        let headers = Some(HashMap::from([
+            ("x-api-key".to_string(), restaurant_key.as_str().to_string()),
+            ("Accept".to_string(), "application/json".to_string()),
+        ]));
+
+        let response = http_request_post(&url, &encrypted, headers)
+            .await
+            .with_context(|| format!("❌ Migros isteği gönderilemedi → {}", url))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .with_context(|| format!("❌ Migros yanıtı okunamadı → {}", url))?;
+
+        if !status.is_success() {
+            return Err(anyhow::Error::new(MigrosError::from_http_status(status.as_u16(), &body)).context(format!(
+                "❌ Migros isteği başarısız, HTTP Status: {} → {} | Yanıt: {}",
+                status, url, body
+            )));
+        }
+
+        serde_json::from_str(&body)
+            .with_context(|| format!("❌ Migros yanıtı çözümlenemedi (yanıt uyumsuz) → {}", url))
+    }
+
+    pub async fn get_menu_details(&self, key_row: &MigrosKeysRow) -> Result<MigrosResponseMenuDetails> {
+        let payload = MigrosPayloadMenuDetails {
+            store_id: key_row.store_id,
+            store_group_id: key_row.chain_id,
+        };
+
+        let restaurant_key = self.row_key(key_row)?;
+
+        self.throttle(key_row.store_id).await;
+        let menu: MigrosResponseMenuDetails = self
+            .post_encrypted(MIGROS_MENU_DETAILS_PATH, &payload, &restaurant_key)
+            .await
+            .with_context(|| format!("❌ Menü detayları alınamadı → store_id: {}", key_row.store_id))?;
+
+        info!(
+            "✅ Menü detayları alındı → store_id: {}, başlık sayısı: {}",
+            key_row.store_id,
+            menu.data.menu_header_infos.len()
+        );
+
+        Ok(menu)
+    }
+
+    pub async fn get_options_details(&self, key_row: &MigrosKeysRow) -> Result<MigrosResponseOptionsDetails> {
+        let payload = MigrosPayloadOptions {
+            menu_id: key_row.menu_id,
+        };
+
+        let restaurant_key = self.row_key(key_row)?;
+
+        self.throttle(key_row.store_id).await;
+        let options: MigrosResponseOptionsDetails = self
+            .post_encrypted(MIGROS_OPTIONS_DETAILS_PATH, &payload, &restaurant_key)
+            .await
+            .with_context(|| {
+                format!(
+                    "❌ Opsiyon detayları alınamadı → store_id: {}, menu_id: {}",
+                    key_row.store_id, key_row.menu_id
+                )
+            })?;
+
+        info!(
+            "✅ Opsiyon detayları alındı → store_id: {}, menu_id: {}, opsiyon satırı: {}",
+            key_row.store_id,
+            key_row.menu_id,
+            flatten_migros_options(&options).len()
+        );
+
+        Ok(options)
+    }
+
+    pub async fn set_product_status(
+        &self,
+        store_id: i64,
+        product_id: i64,
+        status: bool,
+    ) -> Result<MigrosStatusOutcome> {
+        let payload = MigrosPayloadStatusProduct {
+            store_id,
+            product_id,
+            status: migros_status_str(status).to_string(),
+        };
+
+        self.send_change(
+            MIGROS_PRODUCT_STATUS_PATH,
+            &payload,
+            store_id,
+            "product_id",
+            product_id,
+            &payload.status,
+        )
+        .await
+    }
+
+    /// Opsiyon ürününü (sos, içecek vb.) tüm menülerde açar/kapatır; ana ürünlere dokunmaz.
+    pub async fn set_option_status(
+        &self,
+        store_id: i64,
+        option_item_id: i64,
+        status: bool,
+    ) -> Result<MigrosStatusOutcome> {
+        let payload = MigrosPayloadStatusOptions {
+            store_id,
+            option_item_id,
+            status: migros_status_str(status).to_string(),
+        };
+
+        self.send_change(
+            MIGROS_OPTION_STATUS_PATH,
+            &payload,
+            store_id,
+            "option_item_id",
+            option_item_id,
+            &payload.status,
+        )
+        .await
+    }
+
+    /// Ürün fiyatını günceller. Dry-run, journal ve yeniden deneme davranışı durum
+    /// güncellemeleriyle aynıdır.
+    pub async fn set_product_price(
+        &self,
+        store_id: i64,
+        product_id: i64,
+        price: f64,
+    ) -> Result<MigrosStatusOutcome> {
+        let payload = MigrosPayloadPriceProduct {
+            store_id,
+            product_id,
+            price,
+        };
+
+        self.send_change(
+            MIGROS_PRODUCT_PRICE_PATH,
+            &payload,
+            store_id,
+            "product_price",
+            product_id,
+            &price_str(price),
+        )
+        .await
+    }
+
+    /// Değişiklikleri `max_concurrency` eşzamanlı istekle gönderir. Sonuçlar
+    /// `changes` sırasıyla döner.
+    pub async fn set_statuses(
+        &self,
+        changes: &[MigrosStatusChange],
+        max_concurrency: usize,
+    ) -> Vec<Result<MigrosStatusOutcome>> {
+        fan_out(changes.iter().collect(), max_concurrency, "Durum güncelleme", |change| async move {
+            match change.target {
+                MigrosChangeTarget::Product(product_id) => {
+                    self.set_product_status(change.store_id, product_id, change.status).await
+                }
+                MigrosChangeTarget::OptionItem(option_item_id) => {
+                    self.set_option_status(change.store_id, option_item_id, change.status).await
+                }
+            }
+        })
+        .await
+    }
+
+    /// Durum/fiyat isteğini gönderir; tekrar denenebilir hatalarda (sistem hatası, istek limiti,
+    /// bağlantı hatası) artan bekleme süresiyle `max_retries` kadar yeniden dener.
+    async fn send_change<T: serde::Serialize>(
+        &self,
+        path: &str,
+        payload: &T,
+        store_id: i64,
+        target: &str,
+        target_id: i64,
+        value: &str,
+    ) -> Result<MigrosStatusOutcome> {
+        let restaurant_key = self.restaurant_key(store_id)?;
+        let journal_key = migros_journal_key(target, store_id, target_id, value);
+
+        if let Some(journal) = &self.journal {
+            if journal.is_confirmed(&journal_key) {
+                let outcome = MigrosStatusOutcome::AlreadyConfirmed;
+                log_status_outcome(target, store_id, target_id, value, &outcome);
+                return Ok(outcome);
+            }
+
+            journal
+                .record(&journal_key, MigrosJournalState::Planned, store_id, target, target_id, value, None)
+                .await?;
+        }
+
+        if self.dry_run {
+            let planned = MigrosPlannedRequest {
+                store_id,
+                path: path.to_string(),
+                target: target.to_string(),
+                target_id,
+                value: value.to_string(),
+                payload: serde_json::to_value(payload).context("❌ Payload JSON'a çevrilemedi")?,
+                encrypted: self.encrypt_payload(path, payload, restaurant_key)?,
+            };
+            info!("📝 [DRY-RUN] {:?}", planned);
+            self.plan.lock().await.push(planned);
+
+            let outcome = MigrosStatusOutcome::DryRun;
+            log_status_outcome(target, store_id, target_id, value, &outcome);
+            return Ok(outcome);
+        }
+
+        // İstek çıkmadan önce yazılır; çökme sonrası son durumu `Sent` olan kayıtlar
+        // Migros'a ulaşmış olabilir.
+        if let Some(journal) = &self.journal {
+            journal
+                .record(&journal_key, MigrosJournalState::Sent, store_id, target, target_id, value, None)
+                .await?;
+        }
+
+        let mut attempt = 0;
+
+        loop {
+            self.throttle(store_id).await;
+            let result = self
+                .post_encrypted::<T, MigrosActivateResponse>(path, payload, restaurant_key)
+                .await
+                .with_context(|| {
+                    format!(
+                        "❌ Güncellenemedi → store_id: {}, {}: {}",
+                        store_id, target, target_id
+                    )
+                })
+                .map(classify_migros_activate_response);
+
+            let retryable = match &result {
+                Ok(outcome) => MigrosError::from_outcome(outcome).is_some_and(|e| e.is_retryable()),
+                Err(e) => is_retryable_transport_error(e),
+            };
+
+            if !retryable || attempt >= self.max_retries {
+                if let Some(journal) = &self.journal {
+                    let (state, detail) = match &result {
+                        Ok(MigrosStatusOutcome::Success) => (MigrosJournalState::Confirmed, None),
+                        Ok(outcome) => (
+                            MigrosJournalState::Failed,
+                            Some(
+                                MigrosError::from_outcome(outcome)
+                                    .map_or_else(|| format!("{:?}", outcome), |e| e.to_string()),
+                            ),
+                        ),
+                        Err(e) => (MigrosJournalState::Failed, Some(format!("{:#}", e))),
+                    };
+                    if let Err(e) = journal
+                        .record(&journal_key, state, store_id, target, target_id, value, detail)
+                        .await
+                    {
+                        error!("❌ Journal'a yazılamadı → {}, {:#}", journal_key, e);
+                    }
+                }
+
+                let outcome = result?;
+                log_status_outcome(target, store_id, target_id, value, &outcome);
+                return Ok(outcome);
+            }
+
+            attempt += 1;
+            let delay = Duration::from_millis(
+                MIGROS_RETRY_BASE_DELAY_MS
+                    .saturating_mul(2u64.saturating_pow(attempt - 1))
+                    .min(MIGROS_RETRY_MAX_DELAY_MS),
+            );
+            warn!(
+                "⚠️ Tekrar denenecek ({}/{}) → store_id: {}, {}: {}, bekleme: {:?}",
+                attempt, self.max_retries, store_id, target, target_id, delay
+            );
+            sleep(delay).await;
+        }
+    }
+}
+
+impl DeliveryPlatform for MigrosClient {
+    type Menu = MigrosResponseMenuDetails;
+    type Options = MigrosResponseOptionsDetails;
+    type StoreMenu = MigrosStoreMenu;
+
+    fn name(&self) -> &'static str {
+        "Migros"
+    }
+
+    fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    fn bind_cipher(self, factory: PayloadCipherFactory) -> Self {
+        self.with_cipher(factory)
+    }
+
+    fn bind_keys(self, stores: &[MigrosKeysRow]) -> Self {
+        self.with_keys(stores)
+    }
+
+    async fn fetch_menu(&self, store: &MigrosKeysRow) -> Result<Self::Menu> {
+        self.get_menu_details(store).await
+    }
+
+    async fn fetch_options(&self, store: &MigrosKeysRow) -> Result<Self::Options> {
+        self.get_options_details(store).await
+    }
+
+    async fn set_product_status(
+        &self,
+        store_id: i64,
+        product_id: i64,
+        status: bool,
+    ) -> Result<PlatformOutcome> {
+        platform_outcome(MigrosClient::set_product_status(self, store_id, product_id, status).await)
+    }
+
+    async fn set_option_status(
+        &self,
+        store_id: i64,
+        option_item_id: i64,
+        status: bool,
+    ) -> Result<PlatformOutcome> {
+        platform_outcome(MigrosClient::set_option_status(self, store_id, option_item_id, status).await)
+    }
+
+    async fn set_price(
+        &self,
+        store_id: i64,
+        product_id: i64,
+        price: f64,
+    ) -> Result<PlatformOutcome> {
+        platform_outcome(self.set_product_price(store_id, product_id, price).await)
+    }
+
+    fn store_menu(menu: Self::Menu, options: Self::Options) -> Self::StoreMenu {
+        MigrosStoreMenu { menu, options }
+    }
+
+    fn live_statuses(store_menu: &Self::StoreMenu) -> HashMap<PlatformTarget, bool> {
+        let mut live: HashMap<PlatformTarget, bool> = store_menu
+            .menu
+            .data
+            .menu_header_infos
+            .iter()
+            .flat_map(|header| header.food_menu_item_details_dtos.iter())
+            .map(|item| (PlatformTarget::Product(item.product_id), migros_status_bool(&item.status)))
+            .collect();
+
+        for option in flatten_migros_options(&store_menu.options) {
+            live.insert(
+                PlatformTarget::OptionItem(option.option_item_id),
+                migros_status_bool(&option.status),
+            );
+        }
+
+        live
+    }
+
+    fn live_prices(menu: &Self::Menu) -> HashMap<i64, Option<f64>> {
+        menu.data
+            .menu_header_infos
+            .iter()
+            .flat_map(|header| header.food_menu_item_details_dtos.iter())
+            .map(|item| (item.product_id, item.price))
+            .collect()
+    }
+
+    fn product_count(store_menu: &Self::StoreMenu) -> usize {
+        store_menu
+            .menu
+            .data
+            .menu_header_infos
+            .iter()
+            .map(|header| header.food_menu_item_details_dtos.len())
+            .sum()
+    }
+}
+
+/// Migros sonucunu platformdan bağımsız sonuca çevirir. Yanıt gövdesinde ya da HTTP
+/// seviyesinde reddedilen istekler `MigrosError` aksiyonuyla `Rejected` olur; `Err` yalnızca
+/// sınıflandırılamayan hatalar için kalır.
+fn platform_outcome(result: Result<MigrosStatusOutcome>) -> Result<PlatformOutcome> {
+    let outcome = match result {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            return match MigrosError::find_in(&e) {
+                Some(migros_error) => Ok(PlatformOutcome::Rejected {
+                    action: migros_error.action(),
+                    detail: format!("{:#}", e),
+                }),
+                None => Err(e),
+            };
+        }
+    };
+
+    Ok(match outcome {
+        MigrosStatusOutcome::Success => PlatformOutcome::Applied,
+        MigrosStatusOutcome::DryRun => PlatformOutcome::DryRun,
+        MigrosStatusOutcome::AlreadyConfirmed => PlatformOutcome::AlreadyConfirmed,
+        outcome => {
+            let migros_error = MigrosError::from_outcome(&outcome)
+                .ok_or_else(|| anyhow!("❌ Beklenmeyen Migros sonucu → {:?}", outcome))?;
+            PlatformOutcome::Rejected {
+                action: migros_error.action(),
+                detail: migros_error.to_string(),
+            }
+        }
+    })
+}
+
+/// HTTP seviyesindeki hatalardan yalnızca 5xx, 429 ve bağlantı/zaman aşımı hataları
+/// tekrar denenir; 4xx ve yanıt çözümleme hataları tekrar denense de düzelmez.
+fn is_retryable_transport_error(error: &anyhow::Error) -> bool {
+    if let Some(migros_error) = MigrosError::find_in(error) {
+        return migros_error.is_retryable();
+    }
+
+    error
+        .chain()
+        .filter_map(|e| e.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect() || e.is_timeout() || e.is_request())
+}
+
+pub fn classify_migros_activate_response(response: MigrosActivateResponse) -> MigrosStatusOutcome {
+    if response.success {
+        return MigrosStatusOutcome::Success;
+    }
+
+    if let Some(messages) = response.validaton_error_messages.filter(|m| !m.is_empty()) {
+        return MigrosStatusOutcome::ValidationError(messages);
+    }
+
+    match response.error_message {
+        Some(message) if message.is_system_error => MigrosStatusOutcome::SystemError(message),
+        Some(message) => MigrosStatusOutcome::BusinessError(message),
+        None => MigrosStatusOutcome::SystemError(MigrosErrorMessage {
+            error_code: "UNKNOWN".to_string(),
+            error_title: "Bilinmeyen hata".to_string(),
+            error_detail: "success=false döndü ancak hata mesajı yok".to_string(),
+            is_system_error: true,
+        }),
+    }
+}
+
+fn log_status_outcome(
+    target: &str,
+    store_id: i64,
+    target_id: i64,
+    value: &str,
+    outcome: &MigrosStatusOutcome,
+) {
+    match outcome {
+        MigrosStatusOutcome::Success => info!(
+            "✅ Güncellendi → store_id: {}, {}: {}, değer: {}",
+            store_id, target, target_id, value
+        ),
+        MigrosStatusOutcome::BusinessError(e) => warn!(
+            "⚠️ İş kuralı hatası → store_id: {}, {}: {}, [{}] {} - {}",
+            store_id, target, target_id, e.error_code, e.error_title, e.error_detail
+        ),
+        MigrosStatusOutcome::SystemError(e) => error!(
+            "❌ Sistem hatası → store_id: {}, {}: {}, [{}] {} - {}",
+            store_id, target, target_id, e.error_code, e.error_title, e.error_detail
+        ),
+        MigrosStatusOutcome::ValidationError(messages) => warn!(
+            "⚠️ Doğrulama hatası → store_id: {}, {}: {}, mesajlar: {:?}",
+            store_id, target, target_id, messages
+        ),
+        MigrosStatusOutcome::DryRun => info!(
+            "📝 [DRY-RUN] Gönderilmedi → store_id: {}, {}: {}, değer: {}",
+            store_id, target, target_id, value
+        ),
+        MigrosStatusOutcome::AlreadyConfirmed => info!(
+            "⏭️ Journal'da onaylı, atlandı → store_id: {}, {}: {}, değer: {}",
+            store_id, target, target_id, value
+        ),
+    }
+}
+
+/// Opsiyon başlık/ürün ağacını (ürün → opsiyon başlığı → opsiyon ürünü) düz satırlara çevirir.
+pub fn flatten_migros_options(options: &MigrosResponseOptionsDetails) -> Vec<MigrosOptionItemRow> {
+    options
+        .data
+        .iter()
+        .flat_map(|data| data.options_info.object_option_header_infos_v2.iter())
+        .flat_map(|header| {
+            header
+                .object_option_item_infos_v2
+                .iter()
+                .map(move |item| MigrosOptionItemRow {
+                    owner_product_id: header.owner_id,
+                    option_header_id: header.option_header_dto.id,
+                    option_item_id: item.option_item_dto.id,
+                    status: item.option_item_dto.status.clone(),
+                })
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MigrosRateLimits {
+    pub store_min_interval: Duration, // aynı store_id'ye iki istek arası en az süre
+    pub chain_min_interval: Duration, // aynı chain_id'ye iki istek arası en az süre
+}
+
+impl Default for MigrosRateLimits {
+    fn default() -> Self {
+        Self {
+            store_min_interval: Duration::from_millis(MIGROS_DEFAULT_STORE_INTERVAL_MS),
+            chain_min_interval: Duration::from_millis(MIGROS_DEFAULT_CHAIN_INTERVAL_MS),
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+struct MigrosRateSlots {
+    store_next: HashMap<i64, Instant>,
+    chain_next: HashMap<i64, Instant>,
+}
+
+/// Mağaza ve zincir bazında bir sonraki uygun istek zamanını tutan paylaşımlı sınırlayıcı.
+#[derive(Clone, Debug)]
+pub struct MigrosRateLimiter {
+    limits: MigrosRateLimits,
+    slots: Arc<Mutex<MigrosRateSlots>>,
+}
+
+impl MigrosRateLimiter {
+    pub fn new(limits: MigrosRateLimits) -> Self {
+        Self {
+            limits,
+            slots: Arc::new(Mutex::new(MigrosRateSlots::default())),
+        }
+    }
+
+    /// İstek için bir zaman dilimi ayırır ve o zamana kadar bekler.
+    pub async fn acquire(&self, chain_id: Option<i64>, store_id: i64) {
+        let slot = {
+            let mut slots = self.slots.lock().await;
+            let now = Instant::now();
+
+            let mut slot = now;
+            if let Some(next) = slots.store_next.get(&store_id) {
+                slot = slot.max(*next);
+            }
+            if let Some(next) = chain_id.and_then(|chain_id| slots.chain_next.get(&chain_id)) {
+                slot = slot.max(*next);
+            }
+
+            slots.store_next.insert(store_id, slot + self.limits.store_min_interval);
+            if let Some(chain_id) = chain_id {
+                slots.chain_next.insert(chain_id, slot + self.limits.chain_min_interval);
+            }
+
+            slot
+        };
+
+        let wait = slot.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+    }
+}
+
+pub fn migros_journal_key(target: &str, store_id: i64, target_id: i64, value: &str) -> String {
+    format!("{}:{}:{}:{}", target, store_id, target_id, value)
+}
+
+/// JSONL journal dosyasındaki kayıtları yazılma sırasıyla okur. Okunamayan satırlar
+/// uyarı verilerek atlanır.
+pub async fn read_migros_journal(path: &Path) -> Result<Vec<MigrosJournalEntry>> {
+    let file = File::open(path)
+        .await
+        .with_context(|| format!("❌ Journal okunamadı → {:?}", path))?;
+    let mut lines = BufReader::new(file).lines();
+    let mut entries = Vec::new();
+    let mut line_no = 0;
+
+    while let Some(line) = lines.next_line().await? {
+        line_no += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<MigrosJournalEntry>(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => warn!("⚠️ Journal satırı {} okunamadı → {:?}", line_no, e),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Durum isteklerinin planlandı/gönderildi/onaylandı kayıtlarını tutan, yalnızca ekleme
+/// yapılan JSONL journal. `Sent` istek çıkmadan hemen önce yazılır; API'nin reddettiği
+/// ya da iletilemeyen istekler hata detayıyla `Failed` olarak kapanır.
+#[derive(Debug)]
+pub struct MigrosJournal {
+    path: PathBuf,
+    file: Mutex<std::fs::File>,
+    confirmed: HashSet<String>,
+}
+
+impl MigrosJournal {
+    /// Journal dosyasını açar (yoksa oluşturur). `resume` açıkken mevcut kayıtlar okunur;
+    /// bir hedefin (target, store_id, target_id) en son kaydı aynı değerle `Confirmed` ise
+    /// istek bu çalışmada atlanır. Daha sonra başka değer gönderilmiş hedefler atlanmaz
+    /// (ör. kapat → aç → kapat).
+    pub async fn open(path: &Path, resume: bool) -> Result<Self> {
+        let mut latest: HashMap<(String, i64, i64), MigrosJournalEntry> = HashMap::new();
+
+        if resume && fs::try_exists(path).await.unwrap_or(false) {
+            for entry in read_migros_journal(path).await? {
+                latest.insert((entry.target.clone(), entry.store_id, entry.target_id), entry);
+            }
+        }
+
+        let confirmed: HashSet<String> = latest
+            .into_values()
+            .filter(|entry| entry.state == MigrosJournalState::Confirmed)
+            .map(|entry| entry.key)
+            .collect();
+        if resume {
+            info!("✅ Journal yüklendi → {:?}, onaylı istek sayısı: {}", path, confirmed.len());
+        }
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("❌ Journal klasörü oluşturulamadı → {:?}", parent))?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("❌ Journal açılamadı → {:?}", path))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+            confirmed,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_confirmed(&self, key: &str) -> bool {
+        self.confirmed.contains(key)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record(
+        &self,
+        key: &str,
+        state: MigrosJournalState,
+        store_id: i64,
+        target: &str,
+        target_id: i64,
+        value: &str,
+        detail: Option<String>,
+    ) -> Result<()> {
+        let entry = MigrosJournalEntry {
+            key: key.to_string(),
+            state,
+            store_id,
+            target: target.to_string(),
+            target_id,
+            value: value.to_string(),
+            detail,
+            at: chrono::Local::now().to_rfc3339(),
+        };
+        let line = serde_json::to_string(&entry).context("❌ Journal kaydı JSON'a çevrilemedi")?;
+
+        let mut file = self.file.lock().await;
+        writeln!(file, "{}", line).with_context(|| format!("❌ Journal'a yazılamadı → {:?}", self.path))?;
+        file.flush()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result, bail};
+use log::{info, warn};
+use serde_json::Value;
+use tokio::fs;
+
+use crate::schemas::struct_enums::MigrosFeedIssueKind;
+use crate::schemas::struct_migros::{
+    MigrosBranchFeedReport, MigrosBranchJsonRow, MigrosFeedIssue, MigrosKeysRow,
+};
+use crate::schemas::struct_platform::PlatformFeedPrice;
+use crate::services::migros::MIGROS_TYPE_OPTION;
+use crate::utils::http::http_request_get;
+
+/// Branch feed'ini `http(s)://` ile başlıyorsa URL'den, değilse dosyadan okur ve
+/// `data` dizisini ham JSON olarak döner.
+async fn read_migros_branch_feed(source: &str) -> Result<Vec<Value>> {
+    let content = if source.starts_with("http://") || source.starts_with("https://") {
+        let response = http_request_get(source, None)
+            .await
+            .with_context(|| format!("❌ Branch feed alınamadı → {}", source))?;
+
+        if !response.status().is_success() {
+            bail!("❌ Branch feed alınamadı, HTTP Status: {} → {}", response.status(), source);
+        }
+
+        response
+            .text()
+            .await
+            .with_context(|| format!("❌ Branch feed yanıtı okunamadı → {}", source))?
+    } else {
+        fs::read_to_string(source)
+            .await
+            .with_context(|| format!("❌ Branch feed dosyası okunamadı → '{}'", source))?
+    };
+
+    let mut json: Value = serde_json::from_str(&content)
+        .with_context(|| format!("❌ Branch feed JSON formatında değil → {}", source))?;
+
+    match json.get_mut("data").map(Value::take) {
+        Some(Value::Array(rows)) => Ok(rows),
+        _ => bail!("❌ Branch feed içinde 'data' dizisi bulunamadı → {}", source),
+    }
+}
+
+/// Feed'i yükler ve `validate_migros_branch_feed` ile doğrular. Satır bazlı çözümleme
+/// hataları da rapora `InvalidRow` olarak eklenir.
+pub async fn load_migros_branch_feed(
+    source: &str,
+    keys: &[MigrosKeysRow],
+) -> Result<MigrosBranchFeedReport> {
+    let raw_rows = read_migros_branch_feed(source).await?;
+
+    let mut rows = vec![];
+    let mut parse_issues = vec![];
+    for (row_index, raw) in raw_rows.into_iter().enumerate() {
+        match serde_json::from_value::<MigrosBranchJsonRow>(raw) {
+            Ok(row) => rows.push((row_index, row)),
+            Err(e) => parse_issues.push(MigrosFeedIssue {
+                row_index,
+                restaurant_id: None,
+                product_id: None,
+                kind: MigrosFeedIssueKind::InvalidRow,
+                detail: e.to_string(),
+            }),
+        }
+    }
+
+    let mut report = validate_indexed_rows(rows, keys);
+    parse_issues.append(&mut report.issues);
+    parse_issues.sort_by_key(|issue| issue.row_index);
+    report.issues = parse_issues;
+
+    info!(
+        "✅ Branch feed yüklendi → {}, geçerli satır: {}, sorun: {}",
+        source,
+        report.rows.len(),
+        report.issues.len()
+    );
+
+    Ok(report)
+}
+
+/// Feed'in ürün satırlarını `diff_platform_prices` girdisine çevirir; opsiyonlar atlanır.
+pub fn migros_feed_prices(rows: &[MigrosBranchJsonRow]) -> Vec<PlatformFeedPrice> {
+    rows.iter()
+        .filter(|row| !row.r#type.eq_ignore_ascii_case(MIGROS_TYPE_OPTION))
+        .map(|row| PlatformFeedPrice {
+            store_id: row.restaurant_id,
+            product_id: row.product_id,
+            price: row.price,
+        })
+        .collect()
+}
+
+/// Sıfır/negatif fiyat, boş isim, aynı restoranda tekrar eden `product_id` ve anahtar
+/// sheet'inde olmayan `restaurant_key` kontrollerini yapar. Sorunlu satırlar temiz
+/// listeye alınmaz; tekrarlarda ilk satır tutulur.
+pub fn validate_migros_branch_feed(
+    rows: Vec<MigrosBranchJsonRow>,
+    keys: &[MigrosKeysRow],
+) -> MigrosBranchFeedReport {
+    validate_indexed_rows(rows.into_iter().enumerate().collect(), keys)
+}
+
+fn validate_indexed_rows(
+    rows: Vec<(usize, MigrosBranchJsonRow)>,
+    keys: &[MigrosKeysRow],
+) -> MigrosBranchFeedReport {
+    let known_keys: HashSet<&str> = keys.iter().map(|key| key.restaurant_key.as_str()).collect();
+    let mut first_seen: HashMap<(i64, String, i64), usize> = HashMap::new();
+    let mut report = MigrosBranchFeedReport::default();
+
+    for (row_index, row) in rows {
+        let issue = |kind: MigrosFeedIssueKind, detail: String| MigrosFeedIssue {
+            row_index,
+            restaurant_id: Some(row.restaurant_id),
+            product_id: Some(row.product_id),
+            kind,
+            detail,
+        };
+
+        let mut row_issues = vec![];
+
+        if row.price <= 0.0 {
+            row_issues.push(issue(
+                MigrosFeedIssueKind::NonPositivePrice,
+                format!("Fiyat sıfır veya negatif → {}", row.price),
+            ));
+        }
+
+        if row.product_name.trim().is_empty() {
+            row_issues.push(issue(MigrosFeedIssueKind::EmptyName, "Ürün adı boş".to_string()));
+        }
+
+        if !known_keys.contains(row.restaurant_key.as_str()) {
+            row_issues.push(issue(
+                MigrosFeedIssueKind::UnknownRestaurantKey,
+                format!("restaurant_key anahtar sheet'inde yok → integration: '{}'", row.integration_name),
+            ));
+        }
+
+        let duplicate_key = (row.restaurant_id, row.r#type.to_lowercase(), row.product_id);
+        match first_seen.get(&duplicate_key) {
+            Some(first_index) => row_issues.push(issue(
+                MigrosFeedIssueKind::DuplicateProductId,
+                format!("Aynı restoranda tekrar eden product_id, ilk satır → {}", first_index),
+            )),
+            None if row_issues.is_empty() => {
+                first_seen.insert(duplicate_key, row_index);
+            }
+            None => {}
+        }
+
+        if row_issues.is_empty() {
+            report.rows.push(row);
+        } else {
+            for issue in &row_issues {
+                warn!(
+                    "⚠️ Branch feed satırı {} → {:?}: {}",
+                    issue.row_index, issue.kind, issue.detail
+                );
+            }
+            report.issues.append(&mut row_issues);
+        }
+    }
+
+    report
+}
@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+
+use log::info;
+
+use crate::schemas::struct_enums::MigrosChangeTarget;
+use crate::schemas::struct_migros::{
+    MigrosActiveMenu, MigrosBranchJsonRow, MigrosDesiredState, MigrosKeysRow, MigrosStatusChange,
+    MigrosStoreMenu,
+};
+use crate::services::migros::{MIGROS_TYPE_OPTION, flatten_migros_options, migros_status_bool};
+
+/// Hedef durumu canlı Migros menüsü ile karşılaştırıp yalnızca durumu değişmesi gereken
+/// ürün ve opsiyonlar için `MigrosStatusChange` üretir.
+///
+/// Branch feed'deki `restaurant_id` değerinin Migros `store_id` ile aynı olduğu varsayılır.
+pub struct MigrosFlagger<'a> {
+    desired: &'a MigrosDesiredState,
+    // (restaurant_id, opsiyon mu, product_id) → urun_id
+    urun_ids: HashMap<(i64, bool, i64), i64>,
+}
+
+impl<'a> MigrosFlagger<'a> {
+    pub fn new(branch_rows: &[MigrosBranchJsonRow], desired: &'a MigrosDesiredState) -> Self {
+        let urun_ids = branch_rows
+            .iter()
+            .filter_map(|row| {
+                row.urun_id
+                    .map(|urun_id| ((row.restaurant_id, is_option_type(&row.r#type), row.product_id), urun_id))
+            })
+            .collect();
+
+        Self { desired, urun_ids }
+    }
+
+    /// Hedef durumu ve gerekçeyi döner; hedef belirlenemiyorsa (override yok, pencere yok ve
+    /// stok bilgisi yok) `None` döner ve ürüne dokunulmaz. Kapalı zaman penceresi stok
+    /// durumundan bağımsız olarak kapatır. Açık pencerede stok bilgisi varsa karar stoğundur
+    /// (yalnızca açıkça stokta yok ise kapalı kalır); stok bilgisi yoksa ürün açılır.
+    fn desired_status(
+        &self,
+        restaurant_id: i64,
+        target: MigrosChangeTarget,
+        urun_id: Option<i64>,
+    ) -> Option<(bool, String)> {
+        let (overrides, is_option, id) = match target {
+            MigrosChangeTarget::Product(id) => (&self.desired.product_overrides, false, id),
+            MigrosChangeTarget::OptionItem(id) => (&self.desired.option_overrides, true, id),
+        };
+
+        if let Some(status) = overrides.get(&id) {
+            return Some((*status, "manuel override".to_string()));
+        }
+
+        let window = if is_option {
+            None
+        } else {
+            self.desired.product_windows.get(&(restaurant_id, id))
+        };
+        if let Some(window) = window
+            && !window.status
+        {
+            return Some((false, format!("zaman penceresi kapalı ({})", window.rule_names.join(", "))));
+        }
+
+        let urun_id = urun_id.or_else(|| self.urun_ids.get(&(restaurant_id, is_option, id)).copied());
+        let stock = urun_id
+            .and_then(|urun_id| self.desired.urun_status.get(&urun_id).map(|status| (urun_id, *status)));
+
+        match stock {
+            Some((urun_id, true)) => Some((true, format!("urun_id {} stokta", urun_id))),
+            Some((urun_id, false)) => Some((false, format!("urun_id {} stokta yok", urun_id))),
+            None => window.map(|window| {
+                (true, format!("zaman penceresi açık ({})", window.rule_names.join(", ")))
+            }),
+        }
+    }
+
+    fn change_for(
+        &self,
+        store_id: i64,
+        target: MigrosChangeTarget,
+        urun_id: Option<i64>,
+        live_status: bool,
+    ) -> Option<MigrosStatusChange> {
+        let (status, reason) = self.desired_status(store_id, target, urun_id)?;
+        if status == live_status {
+            return None;
+        }
+
+        Some(MigrosStatusChange {
+            store_id,
+            target,
+            status,
+            reason,
+        })
+    }
+
+    /// Canlı menü yanıtına göre gerekli en küçük değişiklik listesini döner.
+    /// Aynı ürün birden fazla başlıkta yer alsa da tek değişiklik üretilir.
+    pub fn diff(&self, key_row: &MigrosKeysRow, store_menu: &MigrosStoreMenu) -> Vec<MigrosStatusChange> {
+        let store_id = key_row.store_id;
+        let mut seen = HashSet::new();
+        let mut changes = vec![];
+
+        for header in &store_menu.menu.data.menu_header_infos {
+            for item in &header.food_menu_item_details_dtos {
+                let target = MigrosChangeTarget::Product(item.product_id);
+                if !seen.insert(target) {
+                    continue;
+                }
+                changes.extend(self.change_for(store_id, target, None, migros_status_bool(&item.status)));
+            }
+        }
+
+        for option in flatten_migros_options(&store_menu.options) {
+            let target = MigrosChangeTarget::OptionItem(option.option_item_id);
+            if !seen.insert(target) {
+                continue;
+            }
+            changes.extend(self.change_for(store_id, target, None, migros_status_bool(&option.status)));
+        }
+
+        info!(
+            "✅ Flagger → store_id: {}, değişecek ürün/opsiyon sayısı: {}",
+            store_id,
+            changes.len()
+        );
+
+        changes
+    }
+
+    /// `MigrosActiveMenu` satırlarında yalnızca durumu değişmesi gerekenlerin `flag` alanını
+    /// `Some(true)` yapar, diğerlerini temizler ve değişiklik listesini döner.
+    pub fn flag_active_menu(&self, rows: &mut [MigrosActiveMenu]) -> Vec<MigrosStatusChange> {
+        let mut seen = HashSet::new();
+        let mut changes = vec![];
+
+        for row in rows.iter_mut() {
+            let target = if is_option_type(&row.r#type) {
+                MigrosChangeTarget::OptionItem(row.product_id)
+            } else {
+                MigrosChangeTarget::Product(row.product_id)
+            };
+
+            let change = self.change_for(row.restaurant_id, target, row.urun_id, row.status);
+            row.flag = change.as_ref().map(|_| true);
+
+            if let Some(change) = change
+                && seen.insert((row.restaurant_id, target))
+            {
+                changes.push(change);
+            }
+        }
+
+        changes
+    }
+}
+
+fn is_option_type(r#type: &str) -> bool {
+    r#type.eq_ignore_ascii_case(MIGROS_TYPE_OPTION)
+}
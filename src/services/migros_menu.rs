@@ -0,0 +1,324 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use log::info;
+
+use crate::schemas::struct_enums::{
+    MigrosChangeSource, MigrosJournalState, MigrosMenuDiffKind, ModifierGroupIdsEnum,
+};
+use crate::schemas::struct_migros::{
+    MigrosActiveMenu, MigrosBranchJsonRow, MigrosJournalEntry, MigrosKeysRow, MigrosMenuDiffEntry,
+    MigrosMenuSnapshotRow, MigrosResponseMenuDetails, MigrosResponseOptionsDetails,
+};
+use crate::services::migros::{
+    MIGROS_TYPE_OPTION, MIGROS_TYPE_PRODUCT, flatten_migros_options, migros_status_bool,
+};
+use crate::services::platform_price::PRICE_EPSILON;
+
+pub const MODIFIER_GROUP_IDS_NONE: &str = "-";
+
+/// Menü ve opsiyon yanıtlarını tek bir düz `MigrosActiveMenu` listesine çevirir.
+///
+/// Ürün satırlarında `modifier_group_ids`, ürünün sahibi olduğu opsiyon başlıklarından
+/// doldurulur. Bir ürün/opsiyon birden fazla başlıkta yer alsa da (restaurant_id,
+/// product_id, type) başına tek satır üretilir; ilk görülen başlık esas alınır.
+pub fn build_migros_active_menu(
+    key_row: &MigrosKeysRow,
+    menu: &MigrosResponseMenuDetails,
+    options: &MigrosResponseOptionsDetails,
+) -> Vec<MigrosActiveMenu> {
+    let option_rows = flatten_migros_options(options);
+
+    let mut modifier_groups: HashMap<i64, BTreeSet<i64>> = HashMap::new();
+    for option in &option_rows {
+        modifier_groups
+            .entry(option.owner_product_id)
+            .or_default()
+            .insert(option.option_header_id);
+    }
+
+    let mut rows = vec![];
+    let mut seen_products = HashSet::new();
+
+    for header in &menu.data.menu_header_infos {
+        for item in &header.food_menu_item_details_dtos {
+            if !seen_products.insert(item.product_id) {
+                continue;
+            }
+
+            let modifier_group_ids = match modifier_groups.get(&item.product_id) {
+                Some(ids) => ModifierGroupIdsEnum::Ids(ids.iter().copied().collect()),
+                None => ModifierGroupIdsEnum::None(MODIFIER_GROUP_IDS_NONE.to_string()),
+            };
+
+            rows.push(MigrosActiveMenu {
+                r#type: MIGROS_TYPE_PRODUCT.to_string(),
+                restaurant_id: key_row.store_id,
+                category_id: header.id,
+                header_info_id: item.id,
+                product_id: item.product_id,
+                modifier_group_ids,
+                status: migros_status_bool(&item.status),
+                urun_id: None,
+                flag: None,
+                x_api_key: key_row.restaurant_key.clone(),
+            });
+        }
+    }
+
+    let mut seen_options = HashSet::new();
+    for header in options
+        .data
+        .iter()
+        .flat_map(|data| data.options_info.object_option_header_infos_v2.iter())
+    {
+        for item in &header.object_option_item_infos_v2 {
+            if !seen_options.insert(item.option_item_dto.id) {
+                continue;
+            }
+
+            rows.push(MigrosActiveMenu {
+                r#type: MIGROS_TYPE_OPTION.to_string(),
+                restaurant_id: key_row.store_id,
+                category_id: header.option_header_dto.id,
+                header_info_id: item.id,
+                product_id: item.option_item_dto.id,
+                modifier_group_ids: ModifierGroupIdsEnum::None(MODIFIER_GROUP_IDS_NONE.to_string()),
+                status: migros_status_bool(&item.option_item_dto.status),
+                urun_id: None,
+                flag: None,
+                x_api_key: key_row.restaurant_key.clone(),
+            });
+        }
+    }
+
+    info!(
+        "✅ Aktif menü oluşturuldu → store_id: {}, ürün: {}, opsiyon: {}",
+        key_row.store_id,
+        seen_products.len(),
+        seen_options.len()
+    );
+
+    rows
+}
+
+/// Branch feed'deki `urun_id` eşleşmelerini (restaurant_id, type, product_id) üzerinden
+/// menü satırlarına işler. Eşleşmesi olmayan satırların `urun_id` değeri `None` kalır.
+pub fn fill_migros_urun_ids(rows: &mut [MigrosActiveMenu], branch_rows: &[MigrosBranchJsonRow]) {
+    let urun_ids: HashMap<(i64, String, i64), i64> = branch_rows
+        .iter()
+        .filter_map(|row| {
+            row.urun_id.map(|urun_id| {
+                (
+                    (row.restaurant_id, row.r#type.to_lowercase(), row.product_id),
+                    urun_id,
+                )
+            })
+        })
+        .collect();
+
+    for row in rows.iter_mut() {
+        row.urun_id = urun_ids
+            .get(&(row.restaurant_id, row.r#type.to_lowercase(), row.product_id))
+            .copied();
+    }
+}
+
+/// Aktif menü satırlarından günlük snapshot üretir; ürün fiyatları menü yanıtından alınır.
+/// `fetched_at`, menünün API'den çekildiği andır.
+pub fn build_migros_menu_snapshot(
+    snapshot_date: NaiveDate,
+    fetched_at: DateTime<Utc>,
+    rows: &[MigrosActiveMenu],
+    menu: &MigrosResponseMenuDetails,
+) -> Vec<MigrosMenuSnapshotRow> {
+    let prices: HashMap<i64, f64> = menu
+        .data
+        .menu_header_infos
+        .iter()
+        .flat_map(|header| header.food_menu_item_details_dtos.iter())
+        .filter_map(|item| item.price.map(|price| (item.product_id, price)))
+        .collect();
+
+    rows.iter()
+        .map(|row| MigrosMenuSnapshotRow {
+            snapshot_date,
+            fetched_at,
+            restaurant_id: row.restaurant_id,
+            r#type: row.r#type.clone(),
+            product_id: row.product_id,
+            category_id: row.category_id,
+            status: row.status,
+            price: if row.r#type == MIGROS_TYPE_PRODUCT {
+                prices.get(&row.product_id).copied()
+            } else {
+                None
+            },
+        })
+        .collect()
+}
+
+fn journal_target_type(target: &str) -> Option<&'static str> {
+    match target {
+        "product_id" => Some(MIGROS_TYPE_PRODUCT),
+        "option_item_id" => Some(MIGROS_TYPE_OPTION),
+        _ => None,
+    }
+}
+
+/// İki snapshot arasındaki farkları (eklenen, kaldırılan, fiyatı değişen, durumu değişen)
+/// listeler. Durum değişiklikleri, iki snapshot'ın çekildiği anlar (`fetched_at`) arasında
+/// journal'da aynı hedefe onaylanmış bir isteğimiz varsa `Us`, yoksa `Platform` olarak
+/// işaretlenir.
+pub fn diff_migros_menu_snapshots(
+    old: &[MigrosMenuSnapshotRow],
+    new: &[MigrosMenuSnapshotRow],
+    journal: &[MigrosJournalEntry],
+) -> Vec<MigrosMenuDiffEntry> {
+    let from_date = old.first().map(|row| row.snapshot_date);
+    let to_date = new.first().map(|row| row.snapshot_date);
+    let (Some(from_date), Some(to_date)) = (from_date.or(to_date), to_date.or(from_date)) else {
+        return vec![];
+    };
+    let from_at = old.iter().map(|row| row.fetched_at).max();
+    let to_at = new.iter().map(|row| row.fetched_at).max();
+    let (Some(from_at), Some(to_at)) = (from_at.or(to_at), to_at.or(from_at)) else {
+        return vec![];
+    };
+
+    let key = |row: &MigrosMenuSnapshotRow| (row.restaurant_id, row.r#type.clone(), row.product_id);
+    let old_map: HashMap<_, &MigrosMenuSnapshotRow> =
+        old.iter().map(|row| (key(row), row)).collect();
+    let new_map: HashMap<_, &MigrosMenuSnapshotRow> =
+        new.iter().map(|row| (key(row), row)).collect();
+
+    // (restaurant_id, type, product_id, status) → bizim gönderip onaylanan istek
+    let ours: HashSet<(i64, &str, i64, bool)> = journal
+        .iter()
+        .filter(|entry| entry.state == MigrosJournalState::Confirmed)
+        .filter(|entry| {
+            DateTime::parse_from_rfc3339(&entry.at)
+                .map(|at| at > from_at && at <= to_at)
+                .unwrap_or(false)
+        })
+        .filter_map(|entry| {
+            journal_target_type(&entry.target).map(|r#type| {
+                (
+                    entry.store_id,
+                    r#type,
+                    entry.target_id,
+                    migros_status_bool(&entry.value),
+                )
+            })
+        })
+        .collect();
+
+    let entry = |restaurant_id: i64, r#type: &str, product_id: i64, kind: MigrosMenuDiffKind| {
+        MigrosMenuDiffEntry {
+            from_date,
+            to_date,
+            restaurant_id,
+            r#type: r#type.to_string(),
+            product_id,
+            kind,
+            old_status: None,
+            new_status: None,
+            old_price: None,
+            new_price: None,
+            changed_by: None,
+        }
+    };
+
+    let mut diffs = vec![];
+
+    for (k, new_row) in &new_map {
+        let Some(old_row) = old_map.get(k) else {
+            diffs.push(MigrosMenuDiffEntry {
+                new_status: Some(new_row.status),
+                new_price: new_row.price,
+                ..entry(
+                    new_row.restaurant_id,
+                    &new_row.r#type,
+                    new_row.product_id,
+                    MigrosMenuDiffKind::Added,
+                )
+            });
+            continue;
+        };
+
+        if old_row.status != new_row.status {
+            let by_us = ours.contains(&(
+                new_row.restaurant_id,
+                new_row.r#type.as_str(),
+                new_row.product_id,
+                new_row.status,
+            ));
+            diffs.push(MigrosMenuDiffEntry {
+                old_status: Some(old_row.status),
+                new_status: Some(new_row.status),
+                changed_by: Some(if by_us {
+                    MigrosChangeSource::Us
+                } else {
+                    MigrosChangeSource::Platform
+                }),
+                ..entry(
+                    new_row.restaurant_id,
+                    &new_row.r#type,
+                    new_row.product_id,
+                    MigrosMenuDiffKind::StatusFlipped,
+                )
+            });
+        }
+
+        let price_changed = match (old_row.price, new_row.price) {
+            (Some(old_price), Some(new_price)) => (old_price - new_price).abs() > PRICE_EPSILON,
+            (None, None) => false,
+            _ => true,
+        };
+        if price_changed {
+            diffs.push(MigrosMenuDiffEntry {
+                old_price: old_row.price,
+                new_price: new_row.price,
+                ..entry(
+                    new_row.restaurant_id,
+                    &new_row.r#type,
+                    new_row.product_id,
+                    MigrosMenuDiffKind::PriceChanged,
+                )
+            });
+        }
+    }
+
+    for (k, old_row) in &old_map {
+        if !new_map.contains_key(k) {
+            diffs.push(MigrosMenuDiffEntry {
+                old_status: Some(old_row.status),
+                old_price: old_row.price,
+                ..entry(
+                    old_row.restaurant_id,
+                    &old_row.r#type,
+                    old_row.product_id,
+                    MigrosMenuDiffKind::Removed,
+                )
+            });
+        }
+    }
+
+    diffs.sort_by_key(|d| {
+        (
+            d.restaurant_id,
+            d.r#type.clone(),
+            d.product_id,
+            d.kind.as_str(),
+        )
+    });
+
+    info!(
+        "✅ Snapshot farkı → {} → {}, fark sayısı: {}",
+        from_date,
+        to_date,
+        diffs.len()
+    );
+
+    diffs
+}
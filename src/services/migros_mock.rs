@@ -0,0 +1,669 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use log::{info, warn};
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::schemas::struct_migros::{MigrosEncryptedPayload, MigrosErrorMessage, MigrosKeysRow};
+use crate::services::migros::{
+    MIGROS_MENU_DETAILS_PATH, MIGROS_OPTION_STATUS_PATH, MIGROS_OPTIONS_DETAILS_PATH,
+    MIGROS_PRODUCT_PRICE_PATH, MIGROS_PRODUCT_STATUS_PATH, MIGROS_STATUS_ACTIVE,
+    MIGROS_STATUS_PASSIVE, migros_status_bool, migros_status_str,
+};
+use crate::utils::encrypt::decrypt_migros;
+
+const MOCK_MAX_REQUEST_BYTES: usize = 1024 * 1024;
+const MOCK_ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Sahte sunucudaki bir ürünün durumu.
+#[derive(Clone, Debug)]
+pub struct MigrosMockProduct {
+    pub header_id: i64,
+    pub item_id: i64,
+    pub status: bool,
+    pub price: f64,
+}
+
+/// Sahte sunucudaki bir opsiyon satırı. Aynı `option_item_id` birden fazla ürüne bağlı
+/// olabilir; durum güncellemesi hepsine uygulanır.
+#[derive(Clone, Debug)]
+pub struct MigrosMockOption {
+    pub owner_product_id: i64,
+    pub option_header_id: i64,
+    pub option_item_id: i64,
+    pub status: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct MigrosMockStore {
+    pub chain_id: i64,
+    pub store_id: i64,
+    pub menu_id: i64,
+    pub restaurant_key: String,
+    pub products: BTreeMap<i64, MigrosMockProduct>,
+    pub options: Vec<MigrosMockOption>,
+}
+
+impl MigrosMockStore {
+    pub fn new(chain_id: i64, store_id: i64, menu_id: i64, restaurant_key: &str) -> Self {
+        Self {
+            chain_id,
+            store_id,
+            menu_id,
+            restaurant_key: restaurant_key.to_string(),
+            products: BTreeMap::new(),
+            options: vec![],
+        }
+    }
+
+    pub fn with_product(
+        mut self,
+        header_id: i64,
+        product_id: i64,
+        status: bool,
+        price: f64,
+    ) -> Self {
+        let item_id = product_id * 10 + self.products.len() as i64;
+        self.products.insert(
+            product_id,
+            MigrosMockProduct {
+                header_id,
+                item_id,
+                status,
+                price,
+            },
+        );
+        self
+    }
+
+    pub fn with_option(
+        mut self,
+        owner_product_id: i64,
+        option_header_id: i64,
+        option_item_id: i64,
+        status: bool,
+    ) -> Self {
+        self.options.push(MigrosMockOption {
+            owner_product_id,
+            option_header_id,
+            option_item_id,
+            status,
+        });
+        self
+    }
+
+    /// Mağaza için `MigrosClient`'a verilebilecek anahtar satırı.
+    pub fn key_row(&self, branch_name: &str) -> MigrosKeysRow {
+        MigrosKeysRow {
+            chain_id: self.chain_id,
+            store_id: self.store_id,
+            menu_id: self.menu_id,
+            brand_name: format!("Mock Marka {}", self.store_id),
+            brand_name_platform: format!("Mock Marka {}", self.store_id),
+            branch_name: branch_name.to_string(),
+            branch_brand_name: format!("{} Mock Marka {}", branch_name, self.store_id),
+            branch_name_platform: branch_name.to_string(),
+            restaurant_key: self.restaurant_key.clone(),
+        }
+    }
+}
+
+/// Enjekte edilebilecek hata türleri.
+#[derive(Clone, Debug)]
+pub enum MigrosMockFailure {
+    /// Gövdesiz HTTP hata kodu (ör. 500, 502).
+    HttpStatus(u16),
+    /// `success: false` ve verilen hata mesajı.
+    Error(MigrosErrorMessage),
+    /// `success: false` ve doğrulama mesajları.
+    Validation(Vec<String>),
+    /// JSON olmayan gövde.
+    MalformedBody,
+    /// Yanıtı geciktirip normal şekilde işler.
+    Delay(Duration),
+}
+
+impl MigrosMockFailure {
+    pub fn error(error_code: &str, is_system_error: bool) -> Self {
+        Self::Error(mock_error_message(error_code, "Mock hata", is_system_error))
+    }
+}
+
+/// `path` (ve verilirse `store_id`) ile eşleşen sonraki `times` isteğe uygulanan hata.
+#[derive(Clone, Debug)]
+pub struct MigrosMockFailureRule {
+    pub path: String,
+    pub store_id: Option<i64>,
+    pub failure: MigrosMockFailure,
+    pub times: usize,
+}
+
+/// Sunucuya gelen ve çözülmüş istek.
+#[derive(Clone, Debug)]
+pub struct MigrosMockRequest {
+    pub path: String,
+    pub api_key: Option<String>,
+    pub payload: Option<Value>,
+}
+
+#[derive(Default)]
+struct MigrosMockState {
+    stores: HashMap<i64, MigrosMockStore>,
+    failures: Vec<MigrosMockFailureRule>,
+    requests: Vec<MigrosMockRequest>,
+}
+
+struct MockResponse {
+    status: u16,
+    body: String,
+}
+
+impl MockResponse {
+    fn json(body: Value) -> Self {
+        Self {
+            status: 200,
+            body: body.to_string(),
+        }
+    }
+}
+
+/// Migros uç noktalarını taklit eden, süreç içi çalışan HTTP sunucusu.
+///
+/// İstekler mağazanın `restaurant_key` değeriyle çözülür, menü durumu bellekte tutulur ve
+/// gerçek API'ye benzer `MigrosActivateResponse` yanıtları döner. `MigrosClient`
+/// `with_base_url(&server.base_url())` ile sunucuya yönlendirilir. Sunucu drop
+/// edildiğinde durur.
+pub struct MigrosMockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MigrosMockState>>,
+    handle: JoinHandle<()>,
+}
+
+impl MigrosMockServer {
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("❌ Mock Migros sunucusu başlatılamadı")?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MigrosMockState::default()));
+
+        let handle = tokio::spawn({
+            let state = state.clone();
+            async move {
+                loop {
+                    let stream = match listener.accept().await {
+                        Ok((stream, _)) => stream,
+                        Err(e) => {
+                            // Ör. dosya tanımlayıcı sınırı; hemen tekrar denemek CPU'yu kilitler
+                            warn!("⚠️ Mock Migros bağlantı kabul edilemedi → {:#}", e);
+                            tokio::time::sleep(MOCK_ACCEPT_BACKOFF).await;
+                            continue;
+                        }
+                    };
+                    let state = state.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, state).await {
+                            warn!("⚠️ Mock Migros bağlantı hatası → {:#}", e);
+                        }
+                    });
+                }
+            }
+        });
+
+        info!("✅ Mock Migros sunucusu başlatıldı → {}", addr);
+        Ok(Self {
+            addr,
+            state,
+            handle,
+        })
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub async fn add_store(&self, store: MigrosMockStore) {
+        self.state.lock().await.stores.insert(store.store_id, store);
+    }
+
+    pub async fn inject_failure(&self, rule: MigrosMockFailureRule) {
+        self.state.lock().await.failures.push(rule);
+    }
+
+    pub async fn clear_failures(&self) {
+        self.state.lock().await.failures.clear();
+    }
+
+    pub async fn requests(&self) -> Vec<MigrosMockRequest> {
+        self.state.lock().await.requests.clone()
+    }
+
+    pub async fn store(&self, store_id: i64) -> Option<MigrosMockStore> {
+        self.state.lock().await.stores.get(&store_id).cloned()
+    }
+
+    pub async fn product_status(&self, store_id: i64, product_id: i64) -> Option<bool> {
+        let state = self.state.lock().await;
+        Some(
+            state
+                .stores
+                .get(&store_id)?
+                .products
+                .get(&product_id)?
+                .status,
+        )
+    }
+
+    pub async fn product_price(&self, store_id: i64, product_id: i64) -> Option<f64> {
+        let state = self.state.lock().await;
+        Some(
+            state
+                .stores
+                .get(&store_id)?
+                .products
+                .get(&product_id)?
+                .price,
+        )
+    }
+
+    pub async fn option_status(&self, store_id: i64, option_item_id: i64) -> Option<bool> {
+        let state = self.state.lock().await;
+        state
+            .stores
+            .get(&store_id)?
+            .options
+            .iter()
+            .find(|option| option.option_item_id == option_item_id)
+            .map(|option| option.status)
+    }
+}
+
+impl Drop for MigrosMockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    state: Arc<Mutex<MigrosMockState>>,
+) -> Result<()> {
+    let (path, headers, body) = read_request(&mut stream).await?;
+    let response = handle_request(&state, &path, &headers, &body).await;
+
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        _ => "Error",
+    };
+    let raw = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        response.body
+    );
+
+    stream.write_all(raw.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// İstek satırını, başlıkları (küçük harfli) ve gövdeyi okur.
+async fn read_request(
+    stream: &mut TcpStream,
+) -> Result<(String, HashMap<String, String>, Vec<u8>)> {
+    let mut buffer = vec![];
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            bail!("❌ Bağlantı istek tamamlanmadan kapandı");
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buffer.len() > MOCK_MAX_REQUEST_BYTES {
+            bail!("❌ İstek başlığı çok büyük");
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .ok_or_else(|| anyhow!("❌ Geçersiz istek satırı → {}", request_line))?
+        .to_string();
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length: usize = headers
+        .get("content-length")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+    if content_length > MOCK_MAX_REQUEST_BYTES {
+        bail!("❌ İstek gövdesi çok büyük → {} byte", content_length);
+    }
+
+    let mut body = buffer[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    Ok((path, headers, body))
+}
+
+async fn handle_request(
+    state: &Mutex<MigrosMockState>,
+    path: &str,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+) -> MockResponse {
+    let api_key = headers.get("x-api-key").cloned();
+    let endpoint = [
+        MIGROS_MENU_DETAILS_PATH,
+        MIGROS_OPTIONS_DETAILS_PATH,
+        MIGROS_PRODUCT_STATUS_PATH,
+        MIGROS_OPTION_STATUS_PATH,
+        MIGROS_PRODUCT_PRICE_PATH,
+    ]
+    .into_iter()
+    .find(|endpoint| path.ends_with(endpoint));
+
+    let payload = api_key.as_deref().and_then(|key| {
+        let encrypted: MigrosEncryptedPayload = serde_json::from_slice(body).ok()?;
+        decrypt_migros::<Value>(&encrypted.value, key).ok()
+    });
+
+    let mut guard = state.lock().await;
+    guard.requests.push(MigrosMockRequest {
+        path: path.to_string(),
+        api_key: api_key.clone(),
+        payload: payload.clone(),
+    });
+
+    let Some(endpoint) = endpoint else {
+        return MockResponse {
+            status: 404,
+            body: json!({ "message": "not found" }).to_string(),
+        };
+    };
+
+    let store_id = payload
+        .as_ref()
+        .and_then(|payload| payload.get("storeId"))
+        .and_then(Value::as_i64);
+
+    if let Some(failure) = take_failure(&mut guard, endpoint, store_id) {
+        match failure {
+            MigrosMockFailure::HttpStatus(status) => {
+                return MockResponse {
+                    status,
+                    body: String::new(),
+                };
+            }
+            MigrosMockFailure::MalformedBody => {
+                return MockResponse {
+                    status: 200,
+                    body: "<html>bakımda</html>".to_string(),
+                };
+            }
+            MigrosMockFailure::Error(message) => return activate_error(message),
+            MigrosMockFailure::Validation(messages) => {
+                return MockResponse::json(json!({
+                    "success": false,
+                    "errorMessage": null,
+                    "validatonErrorMessages": messages,
+                }));
+            }
+            MigrosMockFailure::Delay(delay) => {
+                drop(guard);
+                tokio::time::sleep(delay).await;
+                guard = state.lock().await;
+            }
+        }
+    }
+
+    // Anahtar kontrolü: başlık ve şifreli gövde mağazanın anahtarıyla eşleşmeli.
+    let (Some(api_key), Some(payload)) = (api_key, payload) else {
+        return activate_error(mock_error_message(
+            "UNAUTHORIZED",
+            "x-api-key eksik veya payload çözülemedi",
+            false,
+        ));
+    };
+
+    let store = if endpoint == MIGROS_OPTIONS_DETAILS_PATH {
+        let menu_id = payload.get("menuId").and_then(Value::as_i64);
+        guard
+            .stores
+            .values_mut()
+            .find(|store| Some(store.menu_id) == menu_id && store.restaurant_key == api_key)
+    } else {
+        store_id
+            .and_then(|store_id| guard.stores.get_mut(&store_id))
+            .filter(|store| store.restaurant_key == api_key)
+    };
+
+    let Some(store) = store else {
+        return activate_error(mock_error_message(
+            "UNAUTHORIZED",
+            "Mağaza bulunamadı ya da anahtar uyuşmuyor",
+            false,
+        ));
+    };
+
+    match endpoint {
+        MIGROS_MENU_DETAILS_PATH => MockResponse::json(menu_details_body(store)),
+        MIGROS_OPTIONS_DETAILS_PATH => MockResponse::json(options_details_body(store)),
+        MIGROS_PRODUCT_STATUS_PATH => {
+            let product_id = payload.get("productId").and_then(Value::as_i64);
+            match (parse_status(&payload), product_id) {
+                (Err(response), _) => response,
+                (Ok(status), Some(product_id)) => match store.products.get_mut(&product_id) {
+                    Some(product) => {
+                        product.status = status;
+                        activate_success()
+                    }
+                    None => activate_error(mock_error_message(
+                        "PRODUCT_NOT_FOUND",
+                        &format!("Ürün bulunamadı → {}", product_id),
+                        false,
+                    )),
+                },
+                (Ok(_), None) => validation_error("productId zorunludur"),
+            }
+        }
+        MIGROS_OPTION_STATUS_PATH => {
+            let option_item_id = payload.get("optionItemId").and_then(Value::as_i64);
+            match (parse_status(&payload), option_item_id) {
+                (Err(response), _) => response,
+                (Ok(status), Some(option_item_id)) => {
+                    let mut found = false;
+                    for option in store
+                        .options
+                        .iter_mut()
+                        .filter(|option| option.option_item_id == option_item_id)
+                    {
+                        option.status = status;
+                        found = true;
+                    }
+
+                    if found {
+                        activate_success()
+                    } else {
+                        activate_error(mock_error_message(
+                            "OPTION_ITEM_NOT_FOUND",
+                            &format!("Opsiyon bulunamadı → {}", option_item_id),
+                            false,
+                        ))
+                    }
+                }
+                (Ok(_), None) => validation_error("optionItemId zorunludur"),
+            }
+        }
+        MIGROS_PRODUCT_PRICE_PATH => {
+            let product_id = payload.get("productId").and_then(Value::as_i64);
+            let price = payload.get("price").and_then(Value::as_f64);
+            match (product_id, price) {
+                (_, Some(price)) if price <= 0.0 => {
+                    validation_error("price sıfırdan büyük olmalıdır")
+                }
+                (Some(product_id), Some(price)) => match store.products.get_mut(&product_id) {
+                    Some(product) => {
+                        product.price = price;
+                        activate_success()
+                    }
+                    None => activate_error(mock_error_message(
+                        "PRODUCT_NOT_FOUND",
+                        &format!("Ürün bulunamadı → {}", product_id),
+                        false,
+                    )),
+                },
+                _ => validation_error("productId ve price zorunludur"),
+            }
+        }
+        _ => unreachable!("uç nokta listesi yukarıda sabit"),
+    }
+}
+
+fn take_failure(
+    state: &mut MigrosMockState,
+    endpoint: &str,
+    store_id: Option<i64>,
+) -> Option<MigrosMockFailure> {
+    let index = state.failures.iter().position(|rule| {
+        rule.times > 0
+            && endpoint.ends_with(rule.path.as_str())
+            && (rule.store_id.is_none() || rule.store_id == store_id)
+    })?;
+
+    let rule = &mut state.failures[index];
+    rule.times -= 1;
+    let failure = rule.failure.clone();
+    if rule.times == 0 {
+        state.failures.remove(index);
+    }
+
+    Some(failure)
+}
+
+fn parse_status(payload: &Value) -> Result<bool, MockResponse> {
+    match payload.get("status").and_then(Value::as_str) {
+        Some(status) if status == MIGROS_STATUS_ACTIVE || status == MIGROS_STATUS_PASSIVE => {
+            Ok(migros_status_bool(status))
+        }
+        Some(status) => Err(validation_error(&format!(
+            "status {} veya {} olmalıdır, gelen: {}",
+            MIGROS_STATUS_ACTIVE, MIGROS_STATUS_PASSIVE, status
+        ))),
+        None => Err(validation_error("status zorunludur")),
+    }
+}
+
+fn mock_error_message(error_code: &str, detail: &str, is_system_error: bool) -> MigrosErrorMessage {
+    MigrosErrorMessage {
+        error_code: error_code.to_string(),
+        error_title: error_code.to_string(),
+        error_detail: detail.to_string(),
+        is_system_error,
+    }
+}
+
+fn activate_success() -> MockResponse {
+    MockResponse::json(json!({
+        "success": true,
+        "errorMessage": null,
+        "validatonErrorMessages": null,
+    }))
+}
+
+fn activate_error(message: MigrosErrorMessage) -> MockResponse {
+    MockResponse::json(json!({
+        "success": false,
+        "errorMessage": message,
+        "validatonErrorMessages": null,
+    }))
+}
+
+fn validation_error(message: &str) -> MockResponse {
+    MockResponse::json(json!({
+        "success": false,
+        "errorMessage": null,
+        "validatonErrorMessages": [message],
+    }))
+}
+
+fn menu_details_body(store: &MigrosMockStore) -> Value {
+    let mut headers: BTreeMap<i64, Vec<Value>> = BTreeMap::new();
+    for (product_id, product) in &store.products {
+        headers.entry(product.header_id).or_default().push(json!({
+            "productId": product_id,
+            "status": migros_status_str(product.status),
+            "id": product.item_id,
+            "price": product.price,
+        }));
+    }
+
+    let menu_header_infos: Vec<Value> = headers
+        .into_iter()
+        .map(|(id, items)| json!({ "id": id, "foodMenuItemDetailsDTOs": items }))
+        .collect();
+
+    json!({ "data": { "menuHeaderInfos": menu_header_infos } })
+}
+
+fn options_details_body(store: &MigrosMockStore) -> Value {
+    let mut groups: BTreeMap<(i64, i64), Vec<Value>> = BTreeMap::new();
+    for (i, option) in store.options.iter().enumerate() {
+        groups
+            .entry((option.owner_product_id, option.option_header_id))
+            .or_default()
+            .push(json!({
+                "id": i as i64 + 1,
+                "optionItemDTO": {
+                    "id": option.option_item_id,
+                    "status": migros_status_str(option.status),
+                },
+            }));
+    }
+
+    let header_infos: Vec<Value> = groups
+        .into_iter()
+        .map(|((owner_id, header_id), items)| {
+            json!({
+                "ownerId": owner_id,
+                "optionHeaderDTO": { "id": header_id },
+                "objectOptionItemInfosV2": items,
+            })
+        })
+        .collect();
+
+    json!({
+        "data": [
+            { "optionsInfo": { "objectOptionHeaderInfosV2": header_infos } }
+        ]
+    })
+}
@@ -0,0 +1,330 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use log::{error, info, warn};
+
+use crate::schemas::struct_enums::{
+    PlatformErrorAction, PlatformOutcome, RoofFailurePolicy, RoofPipelinePolicy, RoofStep,
+    RoofStepResult,
+};
+use crate::schemas::struct_platform::{PlatformStatusChange, PlatformStore};
+use crate::services::delivery_platform::{DeliveryPlatform, verify_platform_status_changes};
+use crate::services::store_filter::StoreFilter;
+use crate::utils::concurrency::fan_out;
+
+const ROOF_DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// KeyFiltrele → MenuGetir → Flagger → IstekAt adımlarını her mağaza için sırayla çalıştırır.
+///
+/// Bir mağazada oluşan hata yalnızca o mağazanın kalan adımlarını atlatır; diğer
+/// mağazaların sonuçları rapora eksiksiz yazılır. Mağazalar `max_concurrency` kadar
+/// eşzamanlı işlenir; mağaza/zincir bazlı hız limitleri istemci üzerinden uygulanır.
+///
+/// Pipeline `DeliveryPlatform` uygulayan her platformla çalışır (ör. `MigrosClient`).
+pub struct RoofPipeline<P: DeliveryPlatform> {
+    client: P,
+    policy: RoofPipelinePolicy,
+    max_concurrency: usize,
+    verify_delay: Option<Duration>,
+}
+
+struct IstekAtSummary {
+    applied: Vec<PlatformStatusChange>,
+    sent: usize,
+    skipped: usize,
+    failed: usize,
+    key_alert: bool,
+}
+
+impl<P: DeliveryPlatform> RoofPipeline<P> {
+    pub fn new(client: P) -> Self {
+        Self {
+            client,
+            policy: RoofPipelinePolicy::default(),
+            max_concurrency: ROOF_DEFAULT_MAX_CONCURRENCY,
+            verify_delay: None,
+        }
+    }
+
+    /// IstekAt sonrası `delay` kadar bekleyip uygulanan değişiklikleri canlı menüden
+    /// doğrulayan Dogrula adımını açar. Dry-run'da çalışmaz.
+    pub fn with_verification(mut self, delay: Duration) -> Self {
+        self.verify_delay = Some(delay);
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: RoofPipelinePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Boş filtre tüm mağazaları işler.
+    pub async fn run<F>(
+        &self,
+        stores: &[PlatformStore],
+        key_filter: &StoreFilter,
+        flagger: F,
+    ) -> Vec<RoofStepResult>
+    where
+        F: Fn(&PlatformStore, &P::StoreMenu) -> Result<Vec<PlatformStatusChange>>,
+    {
+        let mut results = vec![];
+
+        let started = Instant::now();
+        let filtered = key_filter.apply(stores);
+        results.push(RoofStepResult {
+            step: RoofStep::KeyFiltrele,
+            store_id: None,
+            result: filtered
+                .as_ref()
+                .map(|_| ())
+                .map_err(|e| format!("{:#}", e)),
+            item_count: filtered.as_ref().map_or(0, |f| f.len()),
+            elapsed: started.elapsed(),
+        });
+
+        let filtered = match filtered {
+            Ok(filtered) => filtered,
+            Err(e) => {
+                error!("❌ KeyFiltrele başarısız → {:#}", e);
+                return results;
+            }
+        };
+
+        let client = self.client.clone().bind_keys(&filtered);
+        let stopped = AtomicBool::new(false);
+
+        let store_results = fan_out(
+            filtered.iter().collect(),
+            self.max_concurrency,
+            "RoofPipeline",
+            |store| {
+                let (client, flagger, stopped) = (&client, &flagger, &stopped);
+                async move {
+                    let mut store_results = vec![];
+                    if stopped.load(Ordering::SeqCst) {
+                        warn!(
+                            "⚠️ Çalışma durdurulduğu için atlandı → store_id: {}",
+                            store.store_id
+                        );
+                        // Atlanan mağaza da raporda görünsün
+                        store_results.push(RoofStepResult {
+                            step: RoofStep::MenuGetir,
+                            store_id: Some(store.store_id),
+                            result: Err("politika gereği atlandı".to_string()),
+                            item_count: 0,
+                            elapsed: Duration::ZERO,
+                        });
+                        return store_results;
+                    }
+
+                    if self
+                        .run_store(client, store, flagger, &mut store_results)
+                        .await
+                    {
+                        warn!(
+                            "⚠️ Politika gereği çalışma durduruldu → store_id: {}",
+                            store.store_id
+                        );
+                        stopped.store(true, Ordering::SeqCst);
+                    }
+                    store_results
+                }
+            },
+        )
+        .await;
+
+        results.extend(store_results.into_iter().flatten());
+
+        log_summary(&results);
+        results
+    }
+
+    /// Mağazanın adımlarını çalıştırır; çalışmanın tamamen durması gerekiyorsa `true` döner.
+    async fn run_store<F>(
+        &self,
+        client: &P,
+        store: &PlatformStore,
+        flagger: &F,
+        results: &mut Vec<RoofStepResult>,
+    ) -> bool
+    where
+        F: Fn(&PlatformStore, &P::StoreMenu) -> Result<Vec<PlatformStatusChange>>,
+    {
+        let store_id = Some(store.store_id);
+
+        // MenuGetir
+        let started = Instant::now();
+        let store_menu = client.fetch_store_menu(store).await;
+        results.push(RoofStepResult {
+            step: RoofStep::MenuGetir,
+            store_id,
+            result: store_menu
+                .as_ref()
+                .map(|_| ())
+                .map_err(|e| format!("{:#}", e)),
+            item_count: store_menu.as_ref().map_or(0, P::product_count),
+            elapsed: started.elapsed(),
+        });
+        let store_menu = match store_menu {
+            Ok(store_menu) => store_menu,
+            Err(e) => {
+                error!(
+                    "❌ MenuGetir başarısız → {} store_id: {}, {:#}",
+                    client.name(),
+                    store.store_id,
+                    e
+                );
+                return self.policy.menu_getir == RoofFailurePolicy::Stop;
+            }
+        };
+
+        // Flagger
+        let started = Instant::now();
+        let changes = flagger(store, &store_menu);
+        results.push(RoofStepResult {
+            step: RoofStep::Flagger,
+            store_id,
+            result: changes.as_ref().map(|_| ()).map_err(|e| format!("{:#}", e)),
+            item_count: changes.as_ref().map_or(0, |c| c.len()),
+            elapsed: started.elapsed(),
+        });
+        let changes = match changes {
+            Ok(changes) => changes,
+            Err(e) => {
+                error!(
+                    "❌ Flagger başarısız → store_id: {}, {:#}",
+                    store.store_id, e
+                );
+                return self.policy.flagger == RoofFailurePolicy::Stop;
+            }
+        };
+
+        // IstekAt
+        let started = Instant::now();
+        let summary = self.send_changes(client, &changes).await;
+        let failed = summary.failed > 0 || summary.key_alert;
+        results.push(RoofStepResult {
+            step: RoofStep::IstekAt,
+            store_id,
+            result: if failed {
+                Err(format!(
+                    "{} istek başarısız, {} istek atlandı{}",
+                    summary.failed,
+                    summary.skipped,
+                    if summary.key_alert {
+                        ", anahtar hatası"
+                    } else {
+                        ""
+                    }
+                ))
+            } else {
+                Ok(())
+            },
+            item_count: summary.sent,
+            elapsed: started.elapsed(),
+        });
+
+        if failed && self.policy.istek_at == RoofFailurePolicy::Stop {
+            return true;
+        }
+
+        // Dogrula
+        if let Some(delay) = self.verify_delay
+            && !client.is_dry_run()
+            && !summary.applied.is_empty()
+        {
+            tokio::time::sleep(delay).await;
+
+            let started = Instant::now();
+            let mismatches =
+                verify_platform_status_changes(client, store, &summary.applied).await;
+            results.push(RoofStepResult {
+                step: RoofStep::Dogrula,
+                store_id,
+                result: match &mismatches {
+                    Ok(m) if m.is_empty() => Ok(()),
+                    Ok(m) => Err(format!("{} değişiklik canlı menüde görünmüyor", m.len())),
+                    Err(e) => Err(format!("{:#}", e)),
+                },
+                item_count: summary.applied.len(),
+                elapsed: started.elapsed(),
+            });
+        }
+
+        false
+    }
+
+    async fn send_changes(&self, client: &P, changes: &[PlatformStatusChange]) -> IstekAtSummary {
+        let mut summary = IstekAtSummary {
+            applied: vec![],
+            sent: 0,
+            skipped: 0,
+            failed: 0,
+            key_alert: false,
+        };
+
+        for change in changes {
+            let (action, detail) = match client.set_status(change).await {
+                Ok(
+                    PlatformOutcome::Applied
+                    | PlatformOutcome::DryRun
+                    | PlatformOutcome::AlreadyConfirmed,
+                ) => {
+                    summary.sent += 1;
+                    summary.applied.push(change.clone());
+                    continue;
+                }
+                Ok(PlatformOutcome::Rejected { action, detail }) => (action, detail),
+                Err(e) => {
+                    error!("❌ İstek gönderilemedi → {:?}, {:#}", change.target, e);
+                    (PlatformErrorAction::Fail, format!("{:#}", e))
+                }
+            };
+
+            match action {
+                PlatformErrorAction::Skip => {
+                    warn!("⚠️ Atlandı → {:?}, {}", change.target, detail);
+                    summary.skipped += 1;
+                }
+                PlatformErrorAction::Alert => {
+                    error!(
+                        "🚨 Anahtar hatası, mağazanın kalan istekleri gönderilmeyecek → store_id: {}, {}",
+                        change.store_id, detail
+                    );
+                    summary.key_alert = true;
+                    break;
+                }
+                PlatformErrorAction::Retry | PlatformErrorAction::Fail => {
+                    summary.failed += 1;
+                    if self.policy.istek_at == RoofFailurePolicy::Stop {
+                        break;
+                    }
+                }
+            }
+        }
+
+        summary
+    }
+}
+
+fn log_summary(results: &[RoofStepResult]) {
+    for result in results {
+        match &result.result {
+            Ok(()) => info!(
+                "✅ {:?} → store_id: {:?}, adet: {}, süre: {:?}",
+                result.step, result.store_id, result.item_count, result.elapsed
+            ),
+            Err(e) => error!(
+                "❌ {:?} → store_id: {:?}, adet: {}, süre: {:?}, hata: {}",
+                result.step, result.store_id, result.item_count, result.elapsed, e
+            ),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use log::info;
+
+use crate::schemas::struct_enums::MigrosMappingConflictKind;
+use crate::schemas::struct_migros::{
+    MigrosActiveMenu, MigrosBranchJsonRow, MigrosInternalProduct, MigrosMappingCandidate,
+    MigrosMappingConflict, MigrosMappingReport, MigrosMissingProduct, MigrosUnmappedProduct,
+};
+use crate::services::migros::MIGROS_TYPE_OPTION;
+use crate::utils::text::normalize_turkish_name;
+
+const CANDIDATE_MIN_SCORE: f64 = 0.4;
+const CANDIDATE_LIMIT: usize = 3;
+const NAME_WEIGHT: f64 = 0.8;
+const CATEGORY_WEIGHT: f64 = 0.2;
+
+fn name_tokens(value: &str) -> HashSet<String> {
+    normalize_turkish_name(value)
+        .split(' ')
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Normalize edilmiş ad kelimeleri üzerinden Jaccard benzerliği (ağırlık 0.8) ve kategori
+/// eşleşmesi (ağırlık 0.2) ile 0-1 arası skor.
+fn match_score(product_name: &str, category_name: &str, internal: &MigrosInternalProduct) -> f64 {
+    let (a, b) = (name_tokens(product_name), name_tokens(&internal.name));
+    let name_score = if a.is_empty() || b.is_empty() {
+        0.0
+    } else if normalize_turkish_name(product_name) == normalize_turkish_name(&internal.name) {
+        1.0
+    } else {
+        a.intersection(&b).count() as f64 / a.union(&b).count() as f64
+    };
+
+    let category_score = if !category_name.trim().is_empty()
+        && normalize_turkish_name(category_name) == normalize_turkish_name(&internal.category_name)
+    {
+        1.0
+    } else {
+        0.0
+    };
+
+    name_score * NAME_WEIGHT + category_score * CATEGORY_WEIGHT
+}
+
+fn suggest_candidates(
+    product_name: &str,
+    category_name: &str,
+    internal: &[MigrosInternalProduct],
+) -> Vec<MigrosMappingCandidate> {
+    let mut candidates: Vec<MigrosMappingCandidate> = internal
+        .iter()
+        .map(|product| MigrosMappingCandidate {
+            urun_id: product.urun_id,
+            name: product.name.clone(),
+            category_name: product.category_name.clone(),
+            score: match_score(product_name, category_name, product),
+        })
+        .filter(|candidate| candidate.score >= CANDIDATE_MIN_SCORE)
+        .collect();
+
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.urun_id.cmp(&b.urun_id)));
+    candidates.truncate(CANDIDATE_LIMIT);
+    candidates
+}
+
+/// İç `urun_id` ile platform `product_id` eşleşmelerini kontrol eder.
+///
+/// - Feed'de ya da `active_menu` içinde eşleşmesi olmayan (veya katalogda olmayan bir
+///   `urun_id`'ye bağlı) platform ürünleri, feed'deki ad/kategori benzerliğine göre aday
+///   önerileriyle listelenir.
+/// - Platformda hiç eşleşmesi olmayan ya da eşleştiği ürün canlı menüde bulunmayan iç
+///   ürünler listelenir. Canlı menü kontrolü yalnızca `active_menu` içinde satırı olan
+///   restoranlar için yapılır.
+/// - Bir restoranda bir `urun_id` birden fazla ürüne ya da bir ürün birden fazla
+///   `urun_id`'ye bağlıysa çakışma olarak raporlanır.
+///
+/// Opsiyon satırları kontrol dışıdır.
+pub fn reconcile_migros_mapping(
+    internal: &[MigrosInternalProduct],
+    branch_rows: &[MigrosBranchJsonRow],
+    active_menu: &[MigrosActiveMenu],
+) -> MigrosMappingReport {
+    let internal_ids: HashMap<i64, &MigrosInternalProduct> =
+        internal.iter().map(|product| (product.urun_id, product)).collect();
+    let products: Vec<&MigrosBranchJsonRow> = branch_rows
+        .iter()
+        .filter(|row| !row.r#type.eq_ignore_ascii_case(MIGROS_TYPE_OPTION))
+        .collect();
+
+    let live_restaurants: HashSet<i64> = active_menu.iter().map(|row| row.restaurant_id).collect();
+    let live_products: HashSet<(i64, i64)> = active_menu
+        .iter()
+        .filter(|row| !row.r#type.eq_ignore_ascii_case(MIGROS_TYPE_OPTION))
+        .map(|row| (row.restaurant_id, row.product_id))
+        .collect();
+
+    let mut report = MigrosMappingReport::default();
+
+    // Eşleşmemiş platform ürünleri: feed ve canlı menü satırları birlikte taranır; canlı
+    // menüde eşleşmesi düşmüş ürünler de raporlanır. Ad/kategori yalnızca feed'den okunur.
+    let feed_rows: HashMap<(i64, i64), &MigrosBranchJsonRow> = products
+        .iter()
+        .map(|row| ((row.restaurant_id, row.product_id), *row))
+        .collect();
+    let is_mapped =
+        |urun_id: Option<i64>| urun_id.is_some_and(|urun_id| internal_ids.contains_key(&urun_id));
+    let unmapped: BTreeSet<(i64, i64)> = products
+        .iter()
+        .filter(|row| !is_mapped(row.urun_id))
+        .map(|row| (row.restaurant_id, row.product_id))
+        .chain(
+            active_menu
+                .iter()
+                .filter(|row| !row.r#type.eq_ignore_ascii_case(MIGROS_TYPE_OPTION))
+                .filter(|row| !is_mapped(row.urun_id))
+                .map(|row| (row.restaurant_id, row.product_id)),
+        )
+        .collect();
+    for (restaurant_id, product_id) in unmapped {
+        let (product_name, category_name) = feed_rows
+            .get(&(restaurant_id, product_id))
+            .map(|row| (row.product_name.clone(), row.category_name.clone()))
+            .unwrap_or_default();
+        report.unmapped_platform.push(MigrosUnmappedProduct {
+            restaurant_id,
+            product_id,
+            candidates: suggest_candidates(&product_name, &category_name, internal),
+            product_name,
+            category_name,
+        });
+    }
+
+    // Platformda olmayan iç ürünler
+    let mut mapped_urun_ids: HashSet<i64> = HashSet::new();
+    for row in &products {
+        let Some(product) = row.urun_id.and_then(|urun_id| internal_ids.get(&urun_id)) else {
+            continue;
+        };
+        mapped_urun_ids.insert(product.urun_id);
+
+        if live_restaurants.contains(&row.restaurant_id)
+            && !live_products.contains(&(row.restaurant_id, row.product_id))
+        {
+            report.missing_on_platform.push(MigrosMissingProduct {
+                urun_id: product.urun_id,
+                name: product.name.clone(),
+                restaurant_id: Some(row.restaurant_id),
+                product_id: Some(row.product_id),
+            });
+        }
+    }
+    for product in internal {
+        if !mapped_urun_ids.contains(&product.urun_id) {
+            report.missing_on_platform.push(MigrosMissingProduct {
+                urun_id: product.urun_id,
+                name: product.name.clone(),
+                restaurant_id: None,
+                product_id: None,
+            });
+        }
+    }
+
+    // Çakışmalar: feed ve canlı menüdeki tüm eşleşmeler birlikte değerlendirilir
+    let mut urun_to_products: HashMap<(i64, i64), BTreeSet<i64>> = HashMap::new();
+    let mut product_to_uruns: HashMap<(i64, i64), BTreeSet<i64>> = HashMap::new();
+    let mappings = products
+        .iter()
+        .filter_map(|row| row.urun_id.map(|urun_id| (row.restaurant_id, row.product_id, urun_id)))
+        .chain(
+            active_menu
+                .iter()
+                .filter(|row| !row.r#type.eq_ignore_ascii_case(MIGROS_TYPE_OPTION))
+                .filter_map(|row| row.urun_id.map(|urun_id| (row.restaurant_id, row.product_id, urun_id))),
+        );
+    for (restaurant_id, product_id, urun_id) in mappings {
+        urun_to_products
+            .entry((restaurant_id, urun_id))
+            .or_default()
+            .insert(product_id);
+        product_to_uruns
+            .entry((restaurant_id, product_id))
+            .or_default()
+            .insert(urun_id);
+    }
+
+    for ((restaurant_id, urun_id), product_ids) in urun_to_products {
+        if product_ids.len() > 1 {
+            report.conflicts.push(MigrosMappingConflict {
+                restaurant_id,
+                kind: MigrosMappingConflictKind::UrunToManyProducts,
+                urun_ids: vec![urun_id],
+                product_ids: product_ids.into_iter().collect(),
+            });
+        }
+    }
+    for ((restaurant_id, product_id), urun_ids) in product_to_uruns {
+        if urun_ids.len() > 1 {
+            report.conflicts.push(MigrosMappingConflict {
+                restaurant_id,
+                kind: MigrosMappingConflictKind::ProductToManyUruns,
+                urun_ids: urun_ids.into_iter().collect(),
+                product_ids: vec![product_id],
+            });
+        }
+    }
+    report
+        .conflicts
+        .sort_by_key(|c| (c.restaurant_id, c.urun_ids.clone(), c.product_ids.clone()));
+
+    info!(
+        "✅ Eşleşme kontrolü → eşleşmemiş platform ürünü: {}, platformda olmayan iç ürün: {}, çakışma: {}",
+        report.unmapped_platform.len(),
+        report.missing_on_platform.len(),
+        report.conflicts.len()
+    );
+
+    report
+}
@@ -0,0 +1,331 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Datelike, Days, FixedOffset, NaiveDate, NaiveDateTime, Utc, Weekday};
+use log::info;
+use tokio::fs;
+
+use crate::schemas::struct_enums::MigrosRuleTarget;
+use crate::schemas::struct_migros::{
+    MigrosActiveMenu, MigrosAvailabilityRule, MigrosAvailabilityRuleSet, MigrosDesiredState,
+    MigrosRuleDecision,
+};
+use crate::services::migros::MIGROS_TYPE_OPTION;
+
+pub async fn load_migros_availability_rules(path: &Path) -> Result<MigrosAvailabilityRuleSet> {
+    let content = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("❌ Kural dosyası okunamadı → {:?}", path))?;
+
+    let rule_set: MigrosAvailabilityRuleSet = serde_json::from_str(&content)
+        .with_context(|| format!("❌ Kural dosyası geçersiz JSON formatında → {:?}", path))?;
+
+    info!(
+        "✅ {} zaman penceresi kuralı yüklendi → {:?}",
+        rule_set.rules.len(),
+        path
+    );
+    Ok(rule_set)
+}
+
+fn effective_weekday(rule_set: &MigrosAvailabilityRuleSet, date: NaiveDate) -> Weekday {
+    match rule_set.holiday_as {
+        Some(weekday) if rule_set.holidays.contains(&date) => weekday,
+        _ => date.weekday(),
+    }
+}
+
+fn is_rule_active(
+    rule_set: &MigrosAvailabilityRuleSet,
+    rule: &MigrosAvailabilityRule,
+    local: NaiveDateTime,
+) -> bool {
+    let date = local.date();
+    let time = local.time();
+    let runs_on = |date: NaiveDate| rule.weekdays.contains(&effective_weekday(rule_set, date));
+
+    if rule.start <= rule.end {
+        runs_on(date) && rule.start <= time && time < rule.end
+    } else {
+        // Gece yarısını aşan pencere: bugün başlayan kısım ya da dün başlayıp bugün biten kısım
+        let yesterday = date.checked_sub_days(Days::new(1));
+        (runs_on(date) && time >= rule.start) || (yesterday.is_some_and(runs_on) && time < rule.end)
+    }
+}
+
+/// Kuralları `at` anında değerlendirip kapsadıkları ürünler ve kategoriler için pencere
+/// durumunu döner.
+///
+/// Bir ürün, kendisini kapsayan kurallardan herhangi biri aktifse açık, hiçbiri aktif
+/// değilse kapalıdır. Ürün bazlı kurallar kategori kurallarını geçersiz kılar. Kategori
+/// kararları, menüde yer alan ve kuralı olan her (restoran, kategori) için ayrıca döner.
+/// Hiçbir kuralın kapsamadığı ürünler ve opsiyonlar sonuca girmez.
+pub fn evaluate_migros_availability_rules(
+    rule_set: &MigrosAvailabilityRuleSet,
+    rows: &[MigrosActiveMenu],
+    at: DateTime<Utc>,
+) -> Result<Vec<MigrosRuleDecision>> {
+    let offset = FixedOffset::east_opt(rule_set.utc_offset_minutes * 60).ok_or_else(|| {
+        anyhow!(
+            "❌ Geçersiz UTC offset → {} dakika",
+            rule_set.utc_offset_minutes
+        )
+    })?;
+    let local = at.with_timezone(&offset).naive_local();
+
+    let mut by_target: HashMap<MigrosRuleTarget, Vec<&MigrosAvailabilityRule>> = HashMap::new();
+    for rule in &rule_set.rules {
+        by_target.entry(rule.target).or_default().push(rule);
+    }
+
+    let decide = |restaurant_id: i64, target: MigrosRuleTarget, rules: &[&MigrosAvailabilityRule]| {
+        let active: Vec<String> = rules
+            .iter()
+            .filter(|rule| is_rule_active(rule_set, rule, local))
+            .map(|rule| rule.name.clone())
+            .collect();
+
+        MigrosRuleDecision {
+            restaurant_id,
+            target,
+            status: !active.is_empty(),
+            rule_names: if active.is_empty() {
+                rules.iter().map(|rule| rule.name.clone()).collect()
+            } else {
+                active
+            },
+        }
+    };
+
+    let mut decisions = vec![];
+    let mut seen_categories = HashSet::new();
+    for row in rows
+        .iter()
+        .filter(|row| !row.r#type.eq_ignore_ascii_case(MIGROS_TYPE_OPTION))
+    {
+        let category = MigrosRuleTarget::Category(row.category_id);
+        if let Some(rules) = by_target.get(&category)
+            && seen_categories.insert((row.restaurant_id, row.category_id))
+        {
+            decisions.push(decide(row.restaurant_id, category, rules));
+        }
+
+        let rules = by_target
+            .get(&MigrosRuleTarget::Product(row.product_id))
+            .or_else(|| by_target.get(&category));
+        let Some(rules) = rules else {
+            continue;
+        };
+
+        decisions.push(decide(
+            row.restaurant_id,
+            MigrosRuleTarget::Product(row.product_id),
+            rules,
+        ));
+    }
+
+    let is_product = |d: &&MigrosRuleDecision| matches!(d.target, MigrosRuleTarget::Product(_));
+    info!(
+        "✅ Zaman penceresi kuralları değerlendirildi → an: {}, kapsanan ürün: {}, açık: {}, kategori: {}",
+        local,
+        decisions.iter().filter(is_product).count(),
+        decisions.iter().filter(is_product).filter(|d| d.status).count(),
+        seen_categories.len()
+    );
+
+    Ok(decisions)
+}
+
+/// Ürün kararlarını Flagger'ın kullandığı zaman penceresi alanına yazar. Kapalı pencere
+/// ürünü kapatır; açık pencere stok bilgisi olmayan ürünü açar, stokta yok olan ürünü
+/// açmaz. Manuel override'lar ve kategori kararları etkilenmez.
+pub fn apply_migros_rule_decisions(
+    desired: &mut MigrosDesiredState,
+    decisions: &[MigrosRuleDecision],
+) {
+    for decision in decisions {
+        if let MigrosRuleTarget::Product(product_id) = decision.target {
+            desired
+                .product_windows
+                .insert((decision.restaurant_id, product_id), decision.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone};
+
+    use crate::schemas::struct_enums::ModifierGroupIdsEnum;
+    use crate::services::migros::MIGROS_TYPE_PRODUCT;
+
+    fn rule(
+        name: &str,
+        target: MigrosRuleTarget,
+        weekdays: &[Weekday],
+        start: &str,
+        end: &str,
+    ) -> MigrosAvailabilityRule {
+        MigrosAvailabilityRule {
+            name: name.to_string(),
+            target,
+            weekdays: weekdays.to_vec(),
+            start: NaiveTime::parse_from_str(start, "%H:%M").unwrap(),
+            end: NaiveTime::parse_from_str(end, "%H:%M").unwrap(),
+        }
+    }
+
+    fn rule_set(rules: Vec<MigrosAvailabilityRule>) -> MigrosAvailabilityRuleSet {
+        MigrosAvailabilityRuleSet {
+            utc_offset_minutes: 180,
+            holidays: vec![],
+            holiday_as: Some(Weekday::Sun),
+            rules,
+        }
+    }
+
+    /// İstanbul saatiyle (UTC+3) verilen anı UTC'ye çevirir.
+    fn istanbul(date: &str, time: &str) -> DateTime<Utc> {
+        let local =
+            NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap();
+        FixedOffset::east_opt(180 * 60)
+            .unwrap()
+            .from_local_datetime(&local)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn is_active_at(rule_set: &MigrosAvailabilityRuleSet, date: &str, time: &str) -> bool {
+        let local =
+            NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap();
+        is_rule_active(rule_set, &rule_set.rules[0], local)
+    }
+
+    fn menu_row(product_id: i64, category_id: i64) -> MigrosActiveMenu {
+        MigrosActiveMenu {
+            r#type: MIGROS_TYPE_PRODUCT.to_string(),
+            restaurant_id: 100,
+            category_id,
+            header_info_id: 1,
+            product_id,
+            modifier_group_ids: ModifierGroupIdsEnum::None("-".to_string()),
+            status: true,
+            urun_id: None,
+            flag: None,
+            x_api_key: String::new(),
+        }
+    }
+
+    #[test]
+    fn window_wraps_past_midnight() {
+        // 2026-10-23 Cuma, 2026-10-22 Perşembe
+        let set = rule_set(vec![rule(
+            "gece",
+            MigrosRuleTarget::Product(1),
+            &[Weekday::Fri],
+            "22:00",
+            "02:00",
+        )]);
+
+        assert!(is_active_at(&set, "2026-10-23", "23:00"));
+        assert!(is_active_at(&set, "2026-10-24", "01:59"));
+        assert!(!is_active_at(&set, "2026-10-24", "02:00"));
+        assert!(!is_active_at(&set, "2026-10-23", "21:59"));
+        // Cuma 01:00 Perşembe gecesine aittir
+        assert!(!is_active_at(&set, "2026-10-23", "01:00"));
+    }
+
+    #[test]
+    fn holiday_uses_holiday_as_weekday() {
+        // 2026-10-29 Perşembe, resmi tatil
+        let mut set = rule_set(vec![rule(
+            "pazar",
+            MigrosRuleTarget::Product(1),
+            &[Weekday::Sun],
+            "10:00",
+            "20:00",
+        )]);
+        set.holidays = vec![NaiveDate::from_ymd_opt(2026, 10, 29).unwrap()];
+
+        assert!(is_active_at(&set, "2026-10-29", "12:00"));
+        assert!(!is_active_at(&set, "2026-10-22", "12:00"));
+
+        set.holiday_as = None;
+        assert!(!is_active_at(&set, "2026-10-29", "12:00"));
+
+        set.rules = vec![rule(
+            "perşembe",
+            MigrosRuleTarget::Product(1),
+            &[Weekday::Thu],
+            "10:00",
+            "20:00",
+        )];
+        assert!(is_active_at(&set, "2026-10-29", "12:00"));
+        set.holiday_as = Some(Weekday::Sun);
+        assert!(!is_active_at(&set, "2026-10-29", "12:00"));
+    }
+
+    #[test]
+    fn equal_start_and_end_is_never_active() {
+        let set = rule_set(vec![rule(
+            "boş",
+            MigrosRuleTarget::Product(1),
+            &[Weekday::Mon],
+            "10:00",
+            "10:00",
+        )]);
+
+        assert!(!is_active_at(&set, "2026-10-19", "09:59"));
+        assert!(!is_active_at(&set, "2026-10-19", "10:00"));
+        assert!(!is_active_at(&set, "2026-10-19", "10:01"));
+    }
+
+    #[test]
+    fn product_rule_overrides_category_rule() {
+        // Kategori penceresi kapalı; ürün 1'in kendi kuralı açık, ürün 2 kategoriye bağlı kalır
+        let set = rule_set(vec![
+            rule(
+                "kahvaltı",
+                MigrosRuleTarget::Category(5),
+                &[Weekday::Mon],
+                "07:00",
+                "11:00",
+            ),
+            rule(
+                "tüm gün",
+                MigrosRuleTarget::Product(1),
+                &[Weekday::Mon],
+                "07:00",
+                "23:00",
+            ),
+        ]);
+        let rows = vec![menu_row(1, 5), menu_row(2, 5), menu_row(3, 6)];
+
+        let decisions =
+            evaluate_migros_availability_rules(&set, &rows, istanbul("2026-10-19", "12:00"))
+                .unwrap();
+        let status = |target: MigrosRuleTarget| {
+            decisions
+                .iter()
+                .find(|decision| decision.target == target)
+                .map(|decision| (decision.status, decision.rule_names.clone()))
+        };
+
+        assert_eq!(
+            status(MigrosRuleTarget::Category(5)),
+            Some((false, vec!["kahvaltı".to_string()]))
+        );
+        assert_eq!(
+            status(MigrosRuleTarget::Product(1)),
+            Some((true, vec!["tüm gün".to_string()]))
+        );
+        assert_eq!(
+            status(MigrosRuleTarget::Product(2)),
+            Some((false, vec!["kahvaltı".to_string()]))
+        );
+        assert_eq!(status(MigrosRuleTarget::Product(3)), None);
+        assert_eq!(decisions.len(), 3);
+    }
+}
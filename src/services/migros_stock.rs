@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use log::{error, info, warn};
+use tokio::fs;
+
+use crate::schemas::struct_enums::{MigrosChangeTarget, MigrosStatusOutcome};
+use crate::schemas::struct_migros::{
+    MigrosBranchJsonRow, MigrosKeysRow, MigrosStatusChange, MigrosStockOutLedger,
+    MigrosStockOutLedgerEntry, MigrosStockOutResult, MigrosStoreFetchError,
+};
+use crate::services::migros::{
+    MIGROS_TYPE_OPTION, MigrosClient, flatten_migros_options, migros_status_bool,
+};
+use crate::services::store_filter::{StoreFilter, TextPattern};
+use crate::utils::text::turkish_fold;
+
+pub async fn load_migros_stock_out_ledger(path: &Path) -> Result<MigrosStockOutLedger> {
+    if !fs::try_exists(path).await.unwrap_or(false) {
+        return Ok(MigrosStockOutLedger::default());
+    }
+
+    let content = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("❌ Stok-out kaydı okunamadı → {:?}", path))?;
+
+    serde_json::from_str(&content)
+        .with_context(|| format!("❌ Stok-out kaydı geçersiz JSON formatında → {:?}", path))
+}
+
+pub async fn save_migros_stock_out_ledger(
+    path: &Path,
+    ledger: &MigrosStockOutLedger,
+) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("❌ Stok-out klasörü oluşturulamadı → {:?}", parent))?;
+    }
+
+    let content =
+        serde_json::to_string_pretty(ledger).context("❌ Stok-out kaydı JSON'a çevrilemedi")?;
+    fs::write(path, content)
+        .await
+        .with_context(|| format!("❌ Stok-out kaydı yazılamadı → {:?}", path))
+}
+
+/// Şubedeki tüm markaların (mutfağı paylaşan kardeş markalar dahil) anahtarları.
+/// Şube adı Türkçe büyük/küçük harf ve boşluk farklarına duyarsız karşılaştırılır.
+fn branch_keys(keys: &[MigrosKeysRow], branch_name: &str) -> Result<Vec<MigrosKeysRow>> {
+    StoreFilter::new()
+        .with_branch(TextPattern::exact(branch_name))
+        .apply(keys)
+}
+
+async fn send_changes(
+    client: &MigrosClient,
+    changes: Vec<MigrosStatusChange>,
+) -> (MigrosStockOutResult, bool) {
+    let mut result = MigrosStockOutResult::default();
+    let mut dry_run = false;
+
+    let outcomes = client.set_statuses(&changes, 1).await;
+    for (change, outcome) in changes.into_iter().zip(outcomes) {
+        match outcome {
+            Ok(MigrosStatusOutcome::Success) => result.applied.push(change),
+            Ok(MigrosStatusOutcome::AlreadyConfirmed) => result.already_confirmed.push(change),
+            Ok(MigrosStatusOutcome::DryRun) => {
+                dry_run = true;
+                result.applied.push(change);
+            }
+            Ok(outcome) => {
+                warn!(
+                    "⚠️ Stok değişikliği uygulanamadı → {:?}, {:?}",
+                    change.target, outcome
+                );
+                result.failed.push(change);
+            }
+            Err(e) => {
+                error!(
+                    "❌ Stok değişikliği gönderilemedi → {:?}, {:#}",
+                    change.target, e
+                );
+                result.failed.push(change);
+            }
+        }
+    }
+
+    (result, dry_run)
+}
+
+/// Mağazanın şu an açık olan ürünlerini; `with_options` ise açık opsiyonlarını da döner.
+async fn active_targets(
+    client: &MigrosClient,
+    key_row: &MigrosKeysRow,
+    with_options: bool,
+) -> Result<HashSet<MigrosChangeTarget>> {
+    let menu = client.get_menu_details(key_row).await?;
+    let mut active: HashSet<MigrosChangeTarget> = menu
+        .data
+        .menu_header_infos
+        .iter()
+        .flat_map(|header| header.food_menu_item_details_dtos.iter())
+        .filter(|item| migros_status_bool(&item.status))
+        .map(|item| MigrosChangeTarget::Product(item.product_id))
+        .collect();
+
+    if with_options {
+        let options = client.get_options_details(key_row).await?;
+        active.extend(
+            flatten_migros_options(&options)
+                .into_iter()
+                .filter(|option| migros_status_bool(&option.status))
+                .map(|option| MigrosChangeTarget::OptionItem(option.option_item_id)),
+        );
+    }
+
+    Ok(active)
+}
+
+/// Bir şubede `urun_id` tükendiğinde, şubedeki tüm markalarda bu ürüne bağlı ve şu an açık
+/// olan Migros ürün/opsiyonlarını kapatır. Kapatılanlar `ledger_path` dosyasına yazılır;
+/// journal'a göre zaten onaylı olanlar `already_confirmed` içinde ayrı raporlanır ve
+/// kayda eklenmez. Dry-run'da dosya güncellenmez. Menüsü çekilemeyen mağazalar `fetch_errors` içinde
+/// raporlanır, diğer mağazalarla devam edilir.
+pub async fn propagate_migros_stock_out(
+    client: &MigrosClient,
+    keys: &[MigrosKeysRow],
+    branch_rows: &[MigrosBranchJsonRow],
+    branch_name: &str,
+    urun_id: i64,
+    ledger_path: &Path,
+) -> Result<MigrosStockOutResult> {
+    let store_keys = branch_keys(keys, branch_name)?;
+    let client = client.clone().with_keys(&store_keys);
+    let mut changes = vec![];
+    let mut fetch_errors = vec![];
+
+    for key_row in &store_keys {
+        let linked: HashSet<MigrosChangeTarget> = branch_rows
+            .iter()
+            .filter(|row| row.restaurant_id == key_row.store_id && row.urun_id == Some(urun_id))
+            .map(|row| {
+                if row.r#type.eq_ignore_ascii_case(MIGROS_TYPE_OPTION) {
+                    MigrosChangeTarget::OptionItem(row.product_id)
+                } else {
+                    MigrosChangeTarget::Product(row.product_id)
+                }
+            })
+            .collect();
+
+        if linked.is_empty() {
+            continue;
+        }
+
+        let with_options = linked
+            .iter()
+            .any(|target| matches!(target, MigrosChangeTarget::OptionItem(_)));
+        let active = match active_targets(&client, key_row, with_options).await {
+            Ok(active) => active,
+            Err(e) => {
+                error!(
+                    "❌ Stok-out için menü alınamadı, mağaza atlandı → store_id: {}, {:#}",
+                    key_row.store_id, e
+                );
+                fetch_errors.push(MigrosStoreFetchError {
+                    store_id: key_row.store_id,
+                    error: format!("{:#}", e),
+                });
+                continue;
+            }
+        };
+
+        changes.extend(
+            linked
+                .intersection(&active)
+                .map(|target| MigrosStatusChange {
+                    store_id: key_row.store_id,
+                    target: *target,
+                    status: false,
+                    reason: format!("urun_id {} stokta yok ({})", urun_id, key_row.brand_name),
+                }),
+        );
+    }
+
+    let (mut result, dry_run) = send_changes(&client, changes).await;
+    result.fetch_errors = fetch_errors;
+
+    if !dry_run && !result.applied.is_empty() {
+        let mut ledger = load_migros_stock_out_ledger(ledger_path).await?;
+        let disabled_at = chrono::Local::now().to_rfc3339();
+        ledger.entries.extend(
+            result
+                .applied
+                .iter()
+                .map(|change| MigrosStockOutLedgerEntry {
+                    branch_name: branch_name.to_string(),
+                    urun_id,
+                    store_id: change.store_id,
+                    target: change.target,
+                    disabled_at: disabled_at.clone(),
+                }),
+        );
+        save_migros_stock_out_ledger(ledger_path, &ledger).await?;
+    }
+
+    info!(
+        "✅ Stok-out → şube: '{}', urun_id: {}, kapatılan: {}, zaten onaylı: {}, başarısız: {}, menüsü alınamayan mağaza: {}",
+        branch_name,
+        urun_id,
+        result.applied.len(),
+        result.already_confirmed.len(),
+        result.failed.len(),
+        result.fetch_errors.len()
+    );
+
+    Ok(result)
+}
+
+/// `urun_id` tekrar stoğa girdiğinde yalnızca stok-out kuralının kapattığı ürünleri açar
+/// ve başarıyla açılanları kayıttan siler.
+pub async fn restock_migros_urun(
+    client: &MigrosClient,
+    keys: &[MigrosKeysRow],
+    branch_name: &str,
+    urun_id: i64,
+    ledger_path: &Path,
+) -> Result<MigrosStockOutResult> {
+    let store_keys = branch_keys(keys, branch_name)?;
+    let client = client.clone().with_keys(&store_keys);
+    let branch = turkish_fold(branch_name);
+
+    let mut ledger = load_migros_stock_out_ledger(ledger_path).await?;
+    let is_ours = |entry: &MigrosStockOutLedgerEntry| {
+        entry.urun_id == urun_id && turkish_fold(&entry.branch_name) == branch
+    };
+
+    let changes: Vec<MigrosStatusChange> = ledger
+        .entries
+        .iter()
+        .filter(|entry| is_ours(entry))
+        .map(|entry| MigrosStatusChange {
+            store_id: entry.store_id,
+            target: entry.target,
+            status: true,
+            reason: format!("urun_id {} tekrar stokta", urun_id),
+        })
+        .collect();
+
+    let (result, dry_run) = send_changes(&client, changes).await;
+
+    // Önceki çalışmada açıldığı onaylanmış kayıtlar da kayıttan düşer
+    if !dry_run && (!result.applied.is_empty() || !result.already_confirmed.is_empty()) {
+        let restored: HashSet<(i64, MigrosChangeTarget)> = result
+            .applied
+            .iter()
+            .chain(&result.already_confirmed)
+            .map(|change| (change.store_id, change.target))
+            .collect();
+        ledger.entries.retain(|entry| {
+            !(is_ours(entry) && restored.contains(&(entry.store_id, entry.target)))
+        });
+        save_migros_stock_out_ledger(ledger_path, &ledger).await?;
+    }
+
+    info!(
+        "✅ Stok girişi → şube: '{}', urun_id: {}, açılan: {}, zaten onaylı: {}, başarısız: {}",
+        branch_name,
+        urun_id,
+        result.applied.len(),
+        result.already_confirmed.len(),
+        result.failed.len()
+    );
+
+    Ok(result)
+}
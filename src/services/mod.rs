@@ -1,4 +1,16 @@
+pub mod delivery_platform;
 pub mod google_api_auth;
 pub mod google_api_sheet;
 pub mod migros;
-pub mod psql;
\ No newline at end of file
+pub mod migros_feed;
+pub mod migros_flagger;
+pub mod migros_menu;
+#[cfg(feature = "test-support")]
+pub mod migros_mock;
+pub mod migros_pipeline;
+pub mod migros_reconcile;
+pub mod migros_rules;
+pub mod migros_stock;
+pub mod platform_price;
+pub mod psql;
+pub mod store_filter;
\ No newline at end of file
@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use log::{info, warn};
+
+use crate::schemas::struct_enums::PlatformOutcome;
+use crate::schemas::struct_platform::{
+    PlatformFeedPrice, PlatformPriceChange, PlatformPriceMismatch, PlatformStore,
+};
+use crate::services::delivery_platform::DeliveryPlatform;
+use crate::utils::concurrency::fan_out;
+
+/// Fiyat karşılaştırmalarında eşit sayılan en büyük fark (kuruşun yarısı).
+pub const PRICE_EPSILON: f64 = 0.005;
+
+/// Journal anahtarı ve loglarda kullanılan fiyat gösterimi.
+pub fn price_str(price: f64) -> String {
+    format!("{:.2}", price)
+}
+
+/// Feed fiyatlarını canlı menüyle karşılaştırıp yalnızca farklı olan ürünler için fiyat
+/// değişikliği üretir. Canlı menüde olmayan ürünler ve sıfır/negatif fiyatlar atlanır.
+pub fn diff_platform_prices<P: DeliveryPlatform>(
+    store: &PlatformStore,
+    menu: &P::Menu,
+    feed_prices: &[PlatformFeedPrice],
+) -> Vec<PlatformPriceChange> {
+    let live = P::live_prices(menu);
+    let mut seen = HashSet::new();
+    let mut changes = vec![];
+
+    for row in feed_prices.iter().filter(|row| row.store_id == store.store_id) {
+        if !seen.insert(row.product_id) {
+            continue;
+        }
+
+        if row.price <= 0.0 {
+            warn!(
+                "⚠️ Geçersiz feed fiyatı atlandı → store_id: {}, product_id: {}, fiyat: {}",
+                store.store_id, row.product_id, row.price
+            );
+            continue;
+        }
+
+        let Some(old_price) = live.get(&row.product_id) else {
+            continue;
+        };
+
+        let changed = old_price.is_none_or(|old_price| (old_price - row.price).abs() > PRICE_EPSILON);
+        if changed {
+            changes.push(PlatformPriceChange {
+                store_id: store.store_id,
+                product_id: row.product_id,
+                old_price: *old_price,
+                price: row.price,
+                reason: format!(
+                    "feed fiyatı {} ≠ canlı fiyat {}",
+                    price_str(row.price),
+                    old_price.map_or("-".to_string(), price_str)
+                ),
+            });
+        }
+    }
+
+    info!(
+        "✅ Fiyat farkı → store_id: {}, değişecek ürün sayısı: {}",
+        store.store_id,
+        changes.len()
+    );
+
+    changes
+}
+
+/// Fiyat değişikliklerini `max_concurrency` eşzamanlı istekle gönderir. Sonuçlar
+/// `changes` sırasıyla döner.
+pub async fn apply_platform_price_changes<P: DeliveryPlatform>(
+    client: &P,
+    changes: &[PlatformPriceChange],
+    max_concurrency: usize,
+) -> Vec<Result<PlatformOutcome>> {
+    fan_out(
+        changes.iter().collect(),
+        max_concurrency,
+        "Fiyat güncelleme",
+        |change| async move {
+            client
+                .set_price(change.store_id, change.product_id, change.price)
+                .await
+        },
+    )
+    .await
+}
+
+/// Canlı menüyü yeniden çekip uygulanan fiyatların görünür olup olmadığını kontrol eder.
+pub async fn verify_platform_price_changes<P: DeliveryPlatform>(
+    client: &P,
+    store: &PlatformStore,
+    changes: &[PlatformPriceChange],
+) -> Result<Vec<PlatformPriceMismatch>> {
+    let changes: Vec<&PlatformPriceChange> = changes
+        .iter()
+        .filter(|change| change.store_id == store.store_id)
+        .collect();
+
+    if changes.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let live = P::live_prices(&client.fetch_menu(store).await?);
+
+    let mismatches: Vec<PlatformPriceMismatch> = changes
+        .iter()
+        .filter_map(|change| {
+            let actual = live.get(&change.product_id).copied().flatten();
+            let matches = actual.is_some_and(|actual| (actual - change.price).abs() <= PRICE_EPSILON);
+            (!matches).then_some(PlatformPriceMismatch {
+                store_id: change.store_id,
+                product_id: change.product_id,
+                expected: change.price,
+                actual,
+            })
+        })
+        .collect();
+
+    for mismatch in &mismatches {
+        warn!(
+            "⚠️ Fiyat canlı menüde görünmüyor → store_id: {}, product_id: {}, beklenen: {}, canlı: {:?}",
+            mismatch.store_id, mismatch.product_id, mismatch.expected, mismatch.actual
+        );
+    }
+
+    info!(
+        "✅ Fiyat doğrulaması tamamlandı → store_id: {}, kontrol edilen: {}, uyumsuz: {}",
+        store.store_id,
+        changes.len(),
+        mismatches.len()
+    );
+
+    Ok(mismatches)
+}
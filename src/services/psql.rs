@@ -1,7 +1,14 @@
+use std::collections::HashSet;
+
 use anyhow::{Context, Result};
-use deadpool_postgres::{Manager, Pool};
+use chrono::{DateTime, NaiveDate, Utc};
+use deadpool_postgres::{Manager, Pool, Transaction};
 use tokio_postgres::{Config, NoTls, Row, types::ToSql};
 
+use crate::schemas::struct_enums::{MigrosChangeSource, MigrosMenuDiffKind, ModifierGroupIdsEnum};
+use crate::schemas::struct_migros::{MigrosActiveMenu, MigrosMenuDiffEntry, MigrosMenuSnapshotRow};
+use crate::services::migros_menu::MODIFIER_GROUP_IDS_NONE;
+
 pub async fn pg_host_client(
     host: &str,
     port: u16,
@@ -101,4 +108,541 @@ pub async fn pg_select_with_query(
 
     println!("✅ SELECT sorgusu başarıyla çalıştırıldı");
     Ok(rows)
-}
\ No newline at end of file
+}
+
+fn modifier_group_ids_to_sql(ids: &ModifierGroupIdsEnum) -> Option<Vec<i64>> {
+    match ids {
+        ModifierGroupIdsEnum::Ids(ids) if !ids.is_empty() => Some(ids.clone()),
+        _ => None,
+    }
+}
+
+fn modifier_group_ids_from_sql(ids: Option<Vec<i64>>) -> ModifierGroupIdsEnum {
+    match ids {
+        Some(ids) if !ids.is_empty() => ModifierGroupIdsEnum::Ids(ids),
+        _ => ModifierGroupIdsEnum::None(MODIFIER_GROUP_IDS_NONE.to_string()),
+    }
+}
+
+/// `MigrosActiveMenu` tablosunu (restaurant_id, product_id, type) doğal anahtarıyla oluşturur.
+/// `modifier_group_ids` "-" yerine NULL olarak tutulur.
+pub async fn pg_create_migros_active_menu_table(pool: &Pool, schema: &str, table: &str) -> Result<()> {
+    let client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+
+    let query = format!(
+        r#"CREATE TABLE IF NOT EXISTS "{}"."{}" (
+            "type" TEXT NOT NULL,
+            "restaurant_id" BIGINT NOT NULL,
+            "category_id" BIGINT NOT NULL,
+            "header_info_id" BIGINT NOT NULL,
+            "product_id" BIGINT NOT NULL,
+            "modifier_group_ids" BIGINT[],
+            "status" BOOLEAN NOT NULL,
+            "urun_id" BIGINT,
+            "flag" BOOLEAN,
+            "x_api_key" TEXT NOT NULL,
+            "deleted" BOOLEAN NOT NULL DEFAULT FALSE,
+            "updated_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+            PRIMARY KEY ("restaurant_id", "product_id", "type")
+        )"#,
+        schema, table
+    );
+
+    client
+        .batch_execute(&query)
+        .await
+        .context("CREATE TABLE sorgusu başarısız")?;
+
+    Ok(())
+}
+
+async fn upsert_migros_active_menu_rows(
+    tx: &Transaction<'_>,
+    schema: &str,
+    table: &str,
+    rows: &[MigrosActiveMenu],
+) -> Result<u64> {
+    let query = format!(
+        r#"INSERT INTO "{}"."{}" AS t ("type", "restaurant_id", "category_id", "header_info_id", "product_id",
+            "modifier_group_ids", "status", "urun_id", "flag", "x_api_key", "deleted", "updated_at")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, FALSE, now())
+        ON CONFLICT ("restaurant_id", "product_id", "type") DO UPDATE SET
+            "category_id" = EXCLUDED."category_id",
+            "header_info_id" = EXCLUDED."header_info_id",
+            "modifier_group_ids" = EXCLUDED."modifier_group_ids",
+            "status" = EXCLUDED."status",
+            "urun_id" = COALESCE(EXCLUDED."urun_id", t."urun_id"),
+            "flag" = EXCLUDED."flag",
+            "x_api_key" = EXCLUDED."x_api_key",
+            "deleted" = FALSE,
+            "updated_at" = now()"#,
+        schema, table
+    );
+
+    let statement = tx
+        .prepare(&query)
+        .await
+        .context("UPSERT sorgusu hazırlanamadı")?;
+
+    let mut affected = 0;
+    for row in rows {
+        let modifier_group_ids = modifier_group_ids_to_sql(&row.modifier_group_ids);
+        affected += tx
+            .execute(
+                &statement,
+                &[
+                    &row.r#type,
+                    &row.restaurant_id,
+                    &row.category_id,
+                    &row.header_info_id,
+                    &row.product_id,
+                    &modifier_group_ids,
+                    &row.status,
+                    &row.urun_id,
+                    &row.flag,
+                    &row.x_api_key,
+                ],
+            )
+            .await
+            .with_context(|| {
+                format!(
+                    "UPSERT sorgusu başarısız → restaurant_id: {}, product_id: {}, type: {}",
+                    row.restaurant_id, row.product_id, row.r#type
+                )
+            })?;
+    }
+
+    Ok(affected)
+}
+
+async fn mark_missing_migros_active_menu_rows(
+    tx: &Transaction<'_>,
+    schema: &str,
+    table: &str,
+    restaurant_id: i64,
+    present_rows: &[MigrosActiveMenu],
+) -> Result<u64> {
+    let (product_ids, types): (Vec<i64>, Vec<String>) = present_rows
+        .iter()
+        .filter(|row| row.restaurant_id == restaurant_id)
+        .map(|row| (row.product_id, row.r#type.clone()))
+        .unzip();
+
+    let query = format!(
+        r#"UPDATE "{}"."{}" AS t SET "deleted" = TRUE, "updated_at" = now()
+        WHERE t."restaurant_id" = $1
+          AND NOT t."deleted"
+          AND NOT EXISTS (
+              SELECT 1 FROM unnest($2::BIGINT[], $3::TEXT[]) AS p("product_id", "type")
+              WHERE p."product_id" = t."product_id" AND p."type" = t."type"
+          )"#,
+        schema, table
+    );
+
+    tx.execute(query.as_str(), &[&restaurant_id, &product_ids, &types])
+        .await
+        .context("Silinmiş olarak işaretleme sorgusu başarısız")
+}
+
+/// Satırları doğal anahtarına göre ekler/günceller. Gelen satırda boş olan `urun_id` mevcut
+/// eşleştirmeyi ezmez; `flag` her zaman gelen değerle yazılır (boş gelirse temizlenir).
+pub async fn pg_upsert_migros_active_menu(
+    pool: &Pool,
+    schema: &str,
+    table: &str,
+    rows: &[MigrosActiveMenu],
+) -> Result<u64> {
+    let mut client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+    let tx = client.transaction().await.context("Transaction başlatılamadı")?;
+
+    let affected = upsert_migros_active_menu_rows(&tx, schema, table, rows).await?;
+    tx.commit().await.context("Transaction commit edilemedi")?;
+
+    println!("✅ {} satır UPSERT edildi", affected);
+    Ok(affected)
+}
+
+/// Mağazanın `present_rows` içinde olmayan satırlarını silinmiş olarak işaretler.
+pub async fn pg_mark_missing_migros_active_menu_deleted(
+    pool: &Pool,
+    schema: &str,
+    table: &str,
+    restaurant_id: i64,
+    present_rows: &[MigrosActiveMenu],
+) -> Result<u64> {
+    let mut client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+    let tx = client.transaction().await.context("Transaction başlatılamadı")?;
+
+    let affected = mark_missing_migros_active_menu_rows(&tx, schema, table, restaurant_id, present_rows).await?;
+    tx.commit().await.context("Transaction commit edilemedi")?;
+
+    println!("✅ {} satır silinmiş olarak işaretlendi", affected);
+    Ok(affected)
+}
+
+/// Mağaza menüsünü tek transaction içinde upsert edip eksik satırları silinmiş olarak
+/// işaretler. `pg_truncate_cascade` + `pg_insert_rows` akışının aksine tablo yükleme
+/// sırasında boş kalmaz.
+pub async fn pg_sync_migros_active_menu(
+    pool: &Pool,
+    schema: &str,
+    table: &str,
+    restaurant_id: i64,
+    rows: &[MigrosActiveMenu],
+) -> Result<(u64, u64)> {
+    let mut client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+    let tx = client.transaction().await.context("Transaction başlatılamadı")?;
+
+    let upserted = upsert_migros_active_menu_rows(&tx, schema, table, rows).await?;
+    let deleted = mark_missing_migros_active_menu_rows(&tx, schema, table, restaurant_id, rows).await?;
+    tx.commit().await.context("Transaction commit edilemedi")?;
+
+    println!(
+        "✅ restaurant_id: {} → {} satır UPSERT edildi, {} satır silinmiş olarak işaretlendi",
+        restaurant_id, upserted, deleted
+    );
+    Ok((upserted, deleted))
+}
+
+/// Mağazanın silinmemiş menü satırlarını yükler.
+pub async fn pg_load_migros_active_menu_by_store(
+    pool: &Pool,
+    schema: &str,
+    table: &str,
+    restaurant_id: i64,
+) -> Result<Vec<MigrosActiveMenu>> {
+    let client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+
+    let query = format!(
+        r#"SELECT "type", "restaurant_id", "category_id", "header_info_id", "product_id",
+            "modifier_group_ids", "status", "urun_id", "flag", "x_api_key"
+        FROM "{}"."{}"
+        WHERE "restaurant_id" = $1 AND NOT "deleted"
+        ORDER BY "type", "product_id""#,
+        schema, table
+    );
+
+    let rows = client
+        .query(query.as_str(), &[&restaurant_id])
+        .await
+        .context("SELECT sorgusu başarısız")?;
+
+    Ok(rows
+        .iter()
+        .map(|row| MigrosActiveMenu {
+            r#type: row.get("type"),
+            restaurant_id: row.get("restaurant_id"),
+            category_id: row.get("category_id"),
+            header_info_id: row.get("header_info_id"),
+            product_id: row.get("product_id"),
+            modifier_group_ids: modifier_group_ids_from_sql(row.get("modifier_group_ids")),
+            status: row.get("status"),
+            urun_id: row.get("urun_id"),
+            flag: row.get("flag"),
+            x_api_key: row.get("x_api_key"),
+        })
+        .collect())
+}
+
+/// Günlük menü snapshot tablosunu ve snapshot farklarının tutulduğu değişiklik geçmişi
+/// tablosunu oluşturur.
+pub async fn pg_create_migros_menu_snapshot_tables(
+    pool: &Pool,
+    schema: &str,
+    snapshot_table: &str,
+    change_table: &str,
+) -> Result<()> {
+    let client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+
+    let query = format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}"."{snapshot_table}" (
+            "snapshot_date" DATE NOT NULL,
+            "fetched_at" TIMESTAMPTZ NOT NULL,
+            "restaurant_id" BIGINT NOT NULL,
+            "type" TEXT NOT NULL,
+            "product_id" BIGINT NOT NULL,
+            "category_id" BIGINT NOT NULL,
+            "status" BOOLEAN NOT NULL,
+            "price" DOUBLE PRECISION,
+            PRIMARY KEY ("snapshot_date", "restaurant_id", "product_id", "type")
+        );
+        CREATE TABLE IF NOT EXISTS "{schema}"."{change_table}" (
+            "id" BIGSERIAL PRIMARY KEY,
+            "from_date" DATE NOT NULL,
+            "to_date" DATE NOT NULL,
+            "restaurant_id" BIGINT NOT NULL,
+            "type" TEXT NOT NULL,
+            "product_id" BIGINT NOT NULL,
+            "kind" TEXT NOT NULL,
+            "old_status" BOOLEAN,
+            "new_status" BOOLEAN,
+            "old_price" DOUBLE PRECISION,
+            "new_price" DOUBLE PRECISION,
+            "changed_by" TEXT,
+            UNIQUE ("to_date", "restaurant_id", "product_id", "type", "kind")
+        );
+        CREATE INDEX IF NOT EXISTS "{change_table}_product_idx"
+            ON "{schema}"."{change_table}" ("restaurant_id", "product_id");"#
+    );
+
+    client
+        .batch_execute(&query)
+        .await
+        .context("CREATE TABLE sorgusu başarısız")?;
+
+    Ok(())
+}
+
+/// Snapshot'ı yazar. Aynı gün ve mağaza için daha önce yazılmış snapshot varsa yenisiyle
+/// değiştirilir.
+pub async fn pg_insert_migros_menu_snapshot(
+    pool: &Pool,
+    schema: &str,
+    table: &str,
+    rows: &[MigrosMenuSnapshotRow],
+) -> Result<u64> {
+    let mut client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+    let tx = client.transaction().await.context("Transaction başlatılamadı")?;
+
+    let delete = format!(
+        r#"DELETE FROM "{}"."{}" WHERE "snapshot_date" = $1 AND "restaurant_id" = $2"#,
+        schema, table
+    );
+    let scopes: HashSet<(NaiveDate, i64)> = rows
+        .iter()
+        .map(|row| (row.snapshot_date, row.restaurant_id))
+        .collect();
+    for (snapshot_date, restaurant_id) in &scopes {
+        tx.execute(delete.as_str(), &[snapshot_date, restaurant_id])
+            .await
+            .context("Eski snapshot silinemedi")?;
+    }
+
+    let insert = format!(
+        r#"INSERT INTO "{}"."{}" ("snapshot_date", "fetched_at", "restaurant_id", "type", "product_id", "category_id", "status", "price")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT DO NOTHING"#,
+        schema, table
+    );
+    let statement = tx
+        .prepare(&insert)
+        .await
+        .context("INSERT sorgusu hazırlanamadı")?;
+
+    let mut affected = 0;
+    for row in rows {
+        affected += tx
+            .execute(
+                &statement,
+                &[
+                    &row.snapshot_date,
+                    &row.fetched_at,
+                    &row.restaurant_id,
+                    &row.r#type,
+                    &row.product_id,
+                    &row.category_id,
+                    &row.status,
+                    &row.price,
+                ],
+            )
+            .await
+            .context("Snapshot INSERT sorgusu başarısız")?;
+    }
+
+    tx.commit().await.context("Transaction commit edilemedi")?;
+
+    println!("✅ {} snapshot satırı yazıldı", affected);
+    Ok(affected)
+}
+
+pub async fn pg_load_migros_menu_snapshot(
+    pool: &Pool,
+    schema: &str,
+    table: &str,
+    restaurant_id: i64,
+    snapshot_date: NaiveDate,
+) -> Result<Vec<MigrosMenuSnapshotRow>> {
+    let client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+
+    let query = format!(
+        r#"SELECT "snapshot_date", "fetched_at", "restaurant_id", "type", "product_id", "category_id", "status", "price"
+        FROM "{}"."{}"
+        WHERE "restaurant_id" = $1 AND "snapshot_date" = $2"#,
+        schema, table
+    );
+
+    let rows = client
+        .query(query.as_str(), &[&restaurant_id, &snapshot_date])
+        .await
+        .context("SELECT sorgusu başarısız")?;
+
+    Ok(rows
+        .iter()
+        .map(|row| MigrosMenuSnapshotRow {
+            snapshot_date: row.get("snapshot_date"),
+            fetched_at: row.get::<_, DateTime<Utc>>("fetched_at"),
+            restaurant_id: row.get("restaurant_id"),
+            r#type: row.get("type"),
+            product_id: row.get("product_id"),
+            category_id: row.get("category_id"),
+            status: row.get("status"),
+            price: row.get("price"),
+        })
+        .collect())
+}
+
+/// Mağazanın `before` tarihinden önceki en son snapshot tarihini döner.
+pub async fn pg_select_previous_migros_snapshot_date(
+    pool: &Pool,
+    schema: &str,
+    table: &str,
+    restaurant_id: i64,
+    before: NaiveDate,
+) -> Result<Option<NaiveDate>> {
+    let client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+
+    let query = format!(
+        r#"SELECT MAX("snapshot_date") AS "snapshot_date" FROM "{}"."{}"
+        WHERE "restaurant_id" = $1 AND "snapshot_date" < $2"#,
+        schema, table
+    );
+
+    let row = client
+        .query_one(query.as_str(), &[&restaurant_id, &before])
+        .await
+        .context("SELECT sorgusu başarısız")?;
+
+    Ok(row.get("snapshot_date"))
+}
+
+pub async fn pg_insert_migros_menu_diff(
+    pool: &Pool,
+    schema: &str,
+    table: &str,
+    entries: &[MigrosMenuDiffEntry],
+) -> Result<u64> {
+    let mut client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+    let tx = client.transaction().await.context("Transaction başlatılamadı")?;
+
+    let query = format!(
+        r#"INSERT INTO "{}"."{}" ("from_date", "to_date", "restaurant_id", "type", "product_id", "kind",
+            "old_status", "new_status", "old_price", "new_price", "changed_by")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT ("to_date", "restaurant_id", "product_id", "type", "kind") DO NOTHING"#,
+        schema, table
+    );
+    let statement = tx
+        .prepare(&query)
+        .await
+        .context("INSERT sorgusu hazırlanamadı")?;
+
+    let mut affected = 0;
+    for entry in entries {
+        let kind = entry.kind.as_str();
+        let changed_by = entry.changed_by.map(|source| source.as_str());
+        affected += tx
+            .execute(
+                &statement,
+                &[
+                    &entry.from_date,
+                    &entry.to_date,
+                    &entry.restaurant_id,
+                    &entry.r#type,
+                    &entry.product_id,
+                    &kind,
+                    &entry.old_status,
+                    &entry.new_status,
+                    &entry.old_price,
+                    &entry.new_price,
+                    &changed_by,
+                ],
+            )
+            .await
+            .context("Değişiklik INSERT sorgusu başarısız")?;
+    }
+
+    tx.commit().await.context("Transaction commit edilemedi")?;
+
+    println!("✅ {} değişiklik kaydı yazıldı", affected);
+    Ok(affected)
+}
+
+/// Bir ürünün değişiklik geçmişini (en yeni önce) döner; "bu ürünü kim, ne zaman kapattı"
+/// sorusunun cevabı.
+pub async fn pg_select_migros_product_history(
+    pool: &Pool,
+    schema: &str,
+    table: &str,
+    restaurant_id: i64,
+    product_id: i64,
+) -> Result<Vec<MigrosMenuDiffEntry>> {
+    let client = pool
+        .get()
+        .await
+        .context("Veritabanı bağlantısı alınamadı")?;
+
+    let query = format!(
+        r#"SELECT "from_date", "to_date", "restaurant_id", "type", "product_id", "kind",
+            "old_status", "new_status", "old_price", "new_price", "changed_by"
+        FROM "{}"."{}"
+        WHERE "restaurant_id" = $1 AND "product_id" = $2
+        ORDER BY "to_date" DESC, "id" DESC"#,
+        schema, table
+    );
+
+    let rows = client
+        .query(query.as_str(), &[&restaurant_id, &product_id])
+        .await
+        .context("SELECT sorgusu başarısız")?;
+
+    rows.iter()
+        .map(|row| {
+            let kind: String = row.get("kind");
+            let changed_by: Option<String> = row.get("changed_by");
+            Ok(MigrosMenuDiffEntry {
+                from_date: row.get("from_date"),
+                to_date: row.get("to_date"),
+                restaurant_id: row.get("restaurant_id"),
+                r#type: row.get("type"),
+                product_id: row.get("product_id"),
+                kind: MigrosMenuDiffKind::parse(&kind)
+                    .with_context(|| format!("Bilinmeyen değişiklik türü → '{}'", kind))?,
+                old_status: row.get("old_status"),
+                new_status: row.get("new_status"),
+                old_price: row.get("old_price"),
+                new_price: row.get("new_price"),
+                changed_by: changed_by.as_deref().and_then(MigrosChangeSource::parse),
+            })
+        })
+        .collect()
+}
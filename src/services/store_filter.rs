@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+use crate::schemas::struct_platform::PlatformStore;
+use crate::utils::text::turkish_fold;
+
+/// Metin alanları için eşleşme kalıbı. Karşılaştırma her zaman `turkish_fold` uygulanmış
+/// değer üzerinde yapılır; "KADIKÖY ", "Kadıköy" ile eşleşir.
+#[derive(Clone, Debug)]
+pub enum TextPattern {
+    /// Tam eşleşme.
+    Exact(String),
+    /// `*` (herhangi bir dizi) ve `?` (tek karakter) destekleyen glob, ör. "kadık*".
+    Glob(Regex),
+    /// Katlanmış (küçük harf, tek boşluk) değere uygulanan regex.
+    Regex(Regex),
+}
+
+impl TextPattern {
+    pub fn exact(value: &str) -> Self {
+        Self::Exact(turkish_fold(value))
+    }
+
+    pub fn glob(pattern: &str) -> Result<Self> {
+        let mut regex = String::from("^");
+        for c in turkish_fold(pattern).chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+
+        let regex = Regex::new(&regex)
+            .with_context(|| format!("❌ Geçersiz glob kalıbı → '{}'", pattern))?;
+        Ok(Self::Glob(regex))
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .with_context(|| format!("❌ Geçersiz regex kalıbı → '{}'", pattern))?;
+        Ok(Self::Regex(regex))
+    }
+
+    /// `*` veya `?` içeren değerleri glob, diğerlerini tam eşleşme olarak yorumlar.
+    pub fn parse(value: &str) -> Result<Self> {
+        if value.contains(['*', '?']) {
+            Self::glob(value)
+        } else {
+            Ok(Self::exact(value))
+        }
+    }
+
+    pub fn is_match(&self, value: &str) -> bool {
+        let value = turkish_fold(value);
+        match self {
+            Self::Exact(expected) => *expected == value,
+            Self::Glob(regex) | Self::Regex(regex) => regex.is_match(&value),
+        }
+    }
+}
+
+impl fmt::Display for TextPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(value) => write!(f, "'{}'", value),
+            Self::Glob(regex) => write!(f, "glob({})", regex.as_str()),
+            Self::Regex(regex) => write!(f, "regex({})", regex.as_str()),
+        }
+    }
+}
+
+/// Mağaza kayıtları için filtre. Dolu olan her kriter sağlanmalıdır (VE); bir kriterin
+/// içindeki değerlerden herhangi birine uymak yeterlidir (VEYA). Boş filtre tüm
+/// mağazaları döner.
+#[derive(Clone, Debug, Default)]
+pub struct StoreFilter {
+    branch_names: Vec<TextPattern>,
+    brand_names: Vec<TextPattern>,
+    platform_names: Vec<TextPattern>,
+    chain_ids: HashSet<i64>,
+    store_ids: HashSet<i64>,
+}
+
+impl StoreFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Şube adları; `*`/`?` içerenler glob olarak yorumlanır.
+    pub fn with_branch_names(mut self, branch_names: &[String]) -> Result<Self> {
+        for branch_name in branch_names {
+            self.branch_names
+                .push(TextPattern::parse(branch_name)?);
+        }
+        Ok(self)
+    }
+
+    pub fn with_branch(mut self, pattern: TextPattern) -> Self {
+        self.branch_names.push(pattern);
+        self
+    }
+
+    /// `brand_name` ve `branch_brand_name` alanlarına bakar.
+    pub fn with_brand(mut self, pattern: TextPattern) -> Self {
+        self.brand_names.push(pattern);
+        self
+    }
+
+    /// `brand_name_platform` ve `branch_name_platform` alanlarına bakar.
+    pub fn with_platform_name(mut self, pattern: TextPattern) -> Self {
+        self.platform_names.push(pattern);
+        self
+    }
+
+    pub fn with_chain_ids(mut self, chain_ids: impl IntoIterator<Item = i64>) -> Self {
+        self.chain_ids.extend(chain_ids);
+        self
+    }
+
+    pub fn with_store_ids(mut self, store_ids: impl IntoIterator<Item = i64>) -> Self {
+        self.store_ids.extend(store_ids);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.branch_names.is_empty()
+            && self.brand_names.is_empty()
+            && self.platform_names.is_empty()
+            && self.chain_ids.is_empty()
+            && self.store_ids.is_empty()
+    }
+
+    pub fn matches(&self, store: &PlatformStore) -> bool {
+        let any = |patterns: &[TextPattern], values: &[&str]| {
+            patterns.is_empty()
+                || patterns
+                    .iter()
+                    .any(|p| values.iter().any(|value| p.is_match(value)))
+        };
+
+        (self.chain_ids.is_empty() || self.chain_ids.contains(&store.chain_id))
+            && (self.store_ids.is_empty() || self.store_ids.contains(&store.store_id))
+            && any(&self.branch_names, &[&store.branch_name])
+            && any(&self.brand_names, &[&store.brand_name, &store.branch_brand_name])
+            && any(
+                &self.platform_names,
+                &[&store.brand_name_platform, &store.branch_name_platform],
+            )
+    }
+
+    /// Filtreye uyan mağazaları döner; hiçbir mağaza uymazsa filtreyi açıklayan bir
+    /// hata döner.
+    pub fn apply(&self, stores: &[PlatformStore]) -> Result<Vec<PlatformStore>> {
+        let filtered: Vec<PlatformStore> = stores
+            .iter()
+            .filter(|store| self.matches(store))
+            .cloned()
+            .collect();
+
+        if filtered.is_empty() {
+            bail!(
+                "❌ Filtreye uyan anahtar bulunamadı → {} (toplam anahtar: {})",
+                self,
+                stores.len()
+            );
+        }
+
+        Ok(filtered)
+    }
+}
+
+impl fmt::Display for StoreFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "filtre yok");
+        }
+
+        let join = |patterns: &[TextPattern]| {
+            patterns
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+        let sorted = |ids: &HashSet<i64>| {
+            let mut ids: Vec<i64> = ids.iter().copied().collect();
+            ids.sort();
+            ids
+        };
+
+        let mut parts = vec![];
+        if !self.branch_names.is_empty() {
+            parts.push(format!("şube: {}", join(&self.branch_names)));
+        }
+        if !self.brand_names.is_empty() {
+            parts.push(format!("marka: {}", join(&self.brand_names)));
+        }
+        if !self.platform_names.is_empty() {
+            parts.push(format!("platform adı: {}", join(&self.platform_names)));
+        }
+        if !self.chain_ids.is_empty() {
+            parts.push(format!("chain_id: {:?}", sorted(&self.chain_ids)));
+        }
+        if !self.store_ids.is_empty() {
+            parts.push(format!("store_id: {:?}", sorted(&self.store_ids)));
+        }
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
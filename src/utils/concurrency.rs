@@ -0,0 +1,46 @@
+use std::future::Future;
+use std::time::Instant;
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::info;
+
+/// `items` üzerinde en fazla `max_concurrency` görevi eşzamanlı çalıştırır, her tamamlanan
+/// görevde ilerlemeyi loglar. Sonuçlar girdi sırasıyla döner.
+pub async fn fan_out<T, R, F, Fut>(
+    items: Vec<T>,
+    max_concurrency: usize,
+    label: &str,
+    task: F,
+) -> Vec<R>
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = R>,
+{
+    let total = items.len();
+    let started = Instant::now();
+    let mut pending = items.into_iter().enumerate();
+    let mut running = FuturesUnordered::new();
+    let mut results: Vec<Option<R>> = (0..total).map(|_| None).collect();
+
+    let run = |(index, item): (usize, T)| {
+        let future = task(item);
+        async move { (index, future.await) }
+    };
+
+    for next in pending.by_ref().take(max_concurrency.max(1)) {
+        running.push(run(next));
+    }
+
+    let mut done = 0;
+    while let Some((index, result)) = running.next().await {
+        results[index] = Some(result);
+        done += 1;
+        info!("⏳ {} → {}/{} tamamlandı, geçen süre: {:?}", label, done, total, started.elapsed());
+
+        if let Some(next) = pending.next() {
+            running.push(run(next));
+        }
+    }
+
+    results.into_iter().flatten().collect()
+}
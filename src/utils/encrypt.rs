@@ -1,34 +1,460 @@
+use std::sync::Arc;
+
 use aes::Aes256;
-use block_modes::{BlockMode, Ecb};
+use aes::cipher::NewBlockCipher;
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use block_modes::{BlockMode, Cbc, Ecb};
 use block_modes::block_padding::Pkcs7;
 use base64::{engine::general_purpose, Engine as _};
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json;
 use log::trace;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 
 type Aes256Ecb = Ecb<Aes256, Pkcs7>;
+type Aes256Cbc = Cbc<Aes256, Pkcs7>;
 
-pub fn encrypt_migros<T: Serialize>(data: &T, secret_key: &str) -> Result<String> {
+const AES_256_KEY_LEN: usize = 32;
+const AES_BLOCK_LEN: usize = 16;
+const GCM_NONCE_LEN: usize = 12;
 
-    // 1. JSON'a çevir
-    let json_string = serde_json::to_string(data)
-        .context("❌ Veri JSON'a çevrilemedi")?;
+/// Payload şifreleme şeması. Platformlar farklı şemalar kullanabilir; JSON ve base64
+/// dönüşümleri `encrypt_payload` / `decrypt_payload` içinde ortaktır.
+pub trait PayloadCipher: Send + Sync {
+    fn encrypt_bytes(&self, plain: &[u8]) -> Result<Vec<u8>>;
+    fn decrypt_bytes(&self, cipher_text: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// AES-256-ECB + PKCS7 (Migros). Anahtar genişletmesi bir kez yapılır; her payload için
+/// yalnızca hazır blok şifreleyici kopyalanır.
+#[derive(Clone)]
+pub struct Aes256EcbCipher {
+    cipher: Aes256,
+}
+
+impl Aes256EcbCipher {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        let cipher = Aes256::new_from_slice(key).map_err(|_| {
+            anyhow!(
+                "❌ Şifreleyici (AES256 ECB) oluşturulamadı, anahtar {} byte ({} olmalı)",
+                key.len(),
+                AES_256_KEY_LEN
+            )
+        })?;
+        Ok(Self { cipher })
+    }
+
+    fn mode(&self) -> Aes256Ecb {
+        Aes256Ecb::new(self.cipher.clone(), &Default::default())
+    }
+}
+
+impl PayloadCipher for Aes256EcbCipher {
+    fn encrypt_bytes(&self, plain: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.mode().encrypt_vec(plain))
+    }
+
+    fn decrypt_bytes(&self, cipher_text: &[u8]) -> Result<Vec<u8>> {
+        self.mode()
+            .decrypt_vec(cipher_text)
+            .context("❌ Veri çözülemedi (AES256 ECB), anahtar uyuşmuyor olabilir")
+    }
+}
+
+/// AES-256-CBC + PKCS7. Sabit IV verilmezse her şifrelemede rastgele IV üretilir ve
+/// şifreli verinin başına eklenir.
+pub struct Aes256CbcCipher {
+    key: Vec<u8>,
+    iv: Option<[u8; AES_BLOCK_LEN]>,
+}
+
+impl Aes256CbcCipher {
+    pub fn new(key: &[u8], iv: Option<[u8; AES_BLOCK_LEN]>) -> Result<Self> {
+        Aes256Cbc::new_from_slices(key, &[0u8; AES_BLOCK_LEN])
+            .context("❌ Şifreleyici (AES256 CBC) oluşturulamadı")?;
+        Ok(Self {
+            key: key.to_vec(),
+            iv,
+        })
+    }
+}
+
+impl PayloadCipher for Aes256CbcCipher {
+    fn encrypt_bytes(&self, plain: &[u8]) -> Result<Vec<u8>> {
+        let (iv, prefix) = match self.iv {
+            Some(iv) => (iv, false),
+            None => (rand::random::<[u8; AES_BLOCK_LEN]>(), true),
+        };
+
+        let cipher = Aes256Cbc::new_from_slices(&self.key, &iv)
+            .context("❌ Şifreleyici (AES256 CBC) oluşturulamadı")?;
+        let cipher_text = cipher.encrypt_vec(plain);
+
+        Ok(if prefix { [iv.as_slice(), &cipher_text].concat() } else { cipher_text })
+    }
+
+    fn decrypt_bytes(&self, cipher_text: &[u8]) -> Result<Vec<u8>> {
+        let (iv, cipher_text) = match &self.iv {
+            Some(iv) => (iv.as_slice(), cipher_text),
+            None => {
+                if cipher_text.len() < AES_BLOCK_LEN {
+                    return Err(anyhow!("❌ Şifreli veri IV için çok kısa (AES256 CBC)"));
+                }
+                cipher_text.split_at(AES_BLOCK_LEN)
+            }
+        };
+
+        let cipher = Aes256Cbc::new_from_slices(&self.key, iv)
+            .context("❌ Şifre çözücü (AES256 CBC) oluşturulamadı")?;
+        cipher
+            .decrypt_vec(cipher_text)
+            .context("❌ Veri çözülemedi (AES256 CBC), anahtar veya IV uyuşmuyor olabilir")
+    }
+}
+
+/// AES-256-GCM. Her şifrelemede rastgele 12 byte nonce üretilir ve şifreli verinin başına
+/// eklenir; doğrulama etiketi (tag) şifreli verinin sonundadır.
+pub struct Aes256GcmCipher {
+    cipher: Aes256Gcm,
+}
+
+impl Aes256GcmCipher {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        let key: [u8; AES_256_KEY_LEN] = key.try_into().map_err(|_| {
+            anyhow!(
+                "❌ Şifreleyici (AES256 GCM) oluşturulamadı, anahtar {} byte ({} olmalı)",
+                key.len(),
+                AES_256_KEY_LEN
+            )
+        })?;
+        Ok(Self {
+            cipher: Aes256Gcm::new(&Key::from(key)),
+        })
+    }
+}
+
+impl PayloadCipher for Aes256GcmCipher {
+    fn encrypt_bytes(&self, plain: &[u8]) -> Result<Vec<u8>> {
+        let nonce = rand::random::<[u8; GCM_NONCE_LEN]>();
+        let cipher_text = self
+            .cipher
+            .encrypt(&Nonce::from(nonce), plain)
+            .map_err(|_| anyhow!("❌ Veri şifrelenemedi (AES256 GCM)"))?;
+
+        Ok([nonce.as_slice(), &cipher_text].concat())
+    }
+
+    fn decrypt_bytes(&self, cipher_text: &[u8]) -> Result<Vec<u8>> {
+        if cipher_text.len() < GCM_NONCE_LEN {
+            return Err(anyhow!("❌ Şifreli veri nonce için çok kısa (AES256 GCM)"));
+        }
+        let (nonce, cipher_text) = cipher_text.split_at(GCM_NONCE_LEN);
+        let nonce: [u8; GCM_NONCE_LEN] = nonce.try_into()?;
+
+        self.cipher
+            .decrypt(&Nonce::from(nonce), cipher_text)
+            .map_err(|_| anyhow!("❌ Veri çözülemedi (AES256 GCM), anahtar uyuşmuyor veya veri bozuk"))
+    }
+}
+
+type CipherFn = dyn Fn(&[u8]) -> Result<Arc<dyn PayloadCipher>> + Send + Sync;
+
+/// Çözülmüş anahtar byte'larından şifreleyici üretir. İstemciler bu fabrika ile platforma
+/// özgü şemayı seçer; varsayılan Migros'un kullandığı AES-256-ECB'dir.
+#[derive(Clone)]
+pub struct PayloadCipherFactory(Arc<CipherFn>);
+
+impl PayloadCipherFactory {
+    pub fn new(factory: impl Fn(&[u8]) -> Result<Arc<dyn PayloadCipher>> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(factory))
+    }
+
+    pub fn aes256_ecb() -> Self {
+        Self::new(|key| Ok(Arc::new(Aes256EcbCipher::new(key)?)))
+    }
 
-    // 2. Byte dizisine çevir
-    let json_bytes = json_string.as_bytes();
+    pub fn aes256_cbc(iv: Option<[u8; AES_BLOCK_LEN]>) -> Self {
+        Self::new(move |key| Ok(Arc::new(Aes256CbcCipher::new(key, iv)?)))
+    }
 
-    // 3. Şifreleyici oluştur
-    let secret_key_bytes = secret_key.as_bytes();
-    let cipher = Aes256Ecb::new_from_slices(secret_key_bytes, &[])
-        .context("❌ Şifreleyici (AES256 ECB) oluşturulamadı")?;
+    pub fn aes256_gcm() -> Self {
+        Self::new(|key| Ok(Arc::new(Aes256GcmCipher::new(key)?)))
+    }
 
-    // 4. Şifreleme
-    let cipher_text = cipher.encrypt_vec(json_bytes);
+    pub fn cipher(&self, key: &[u8]) -> Result<Arc<dyn PayloadCipher>> {
+        (self.0)(key)
+    }
+}
+
+impl Default for PayloadCipherFactory {
+    fn default() -> Self {
+        Self::aes256_ecb()
+    }
+}
 
-    // 5. Base64'e çevir
+impl std::fmt::Debug for PayloadCipherFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PayloadCipherFactory")
+    }
+}
+
+/// Veriyi JSON'a çevirip verilen şema ile şifreler ve base64 döner.
+pub fn encrypt_payload<T: Serialize>(data: &T, cipher: &dyn PayloadCipher) -> Result<String> {
+    let json_string = serde_json::to_string(data)
+        .context("❌ Veri JSON'a çevrilemedi")?;
+    let cipher_text = cipher.encrypt_bytes(json_string.as_bytes())?;
     let encoded = general_purpose::STANDARD.encode(cipher_text);
     trace!("Data: {:?}, Encrypted Key: {:?}", json_string, encoded);
 
     Ok(encoded)
 }
+
+/// `encrypt_payload` karşılığı: base64 çözer, şifreyi açar ve JSON'dan `T`'ye çevirir.
+pub fn decrypt_payload<T: DeserializeOwned>(encoded: &str, cipher: &dyn PayloadCipher) -> Result<T> {
+    let cipher_text = general_purpose::STANDARD
+        .decode(encoded.trim())
+        .context("❌ Şifreli veri base64 çözülemedi")?;
+    let plain = cipher.decrypt_bytes(&cipher_text)?;
+    trace!("Decrypted: {:?}", String::from_utf8_lossy(&plain));
+
+    serde_json::from_slice(&plain).context("❌ Çözülen veri beklenen JSON yapısına uymuyor")
+}
+
+/// Migros mağaza anahtarı. Uzunluk `parse` sırasında doğrulanır ve şifreleyici bir kez
+/// kurulup saklanır; aynı mağaza için binlerce payload şifrelenirken şifreleyici tekrar
+/// kurulmaz.
+///
+/// Kabul edilen biçimler (öncelik sırasıyla): 32 byte ham metin, 64 karakter hex, 32 byte'a
+/// çözülen base64.
+#[derive(Clone)]
+pub struct MigrosKey {
+    // x-api-key başlığında sheet'teki haliyle gönderilir
+    value: String,
+    cipher: Arc<dyn PayloadCipher>,
+}
+
+impl MigrosKey {
+    /// Varsayılan şema (AES-256-ECB) ile anahtarı çözer.
+    pub fn parse(value: &str) -> Result<Self> {
+        Self::parse_with(value, &PayloadCipherFactory::aes256_ecb())
+    }
+
+    pub fn parse_with(value: &str, factory: &PayloadCipherFactory) -> Result<Self> {
+        let value = value.trim();
+        let key_bytes = Self::decode(value)?;
+
+        Ok(Self {
+            value: value.to_string(),
+            cipher: factory.cipher(&key_bytes)?,
+        })
+    }
+
+    fn decode(value: &str) -> Result<Vec<u8>> {
+        if value.len() == AES_256_KEY_LEN {
+            return Ok(value.as_bytes().to_vec());
+        }
+
+        if value.len() == AES_256_KEY_LEN * 2 && value.chars().all(|c| c.is_ascii_hexdigit()) {
+            return (0..value.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&value[i..i + 2], 16))
+                .collect::<Result<Vec<u8>, _>>()
+                .context("❌ Hex anahtar çözülemedi");
+        }
+
+        if let Ok(decoded) = general_purpose::STANDARD.decode(value)
+            && decoded.len() == AES_256_KEY_LEN
+        {
+            return Ok(decoded);
+        }
+
+        Err(anyhow!(
+            "❌ Geçersiz Migros anahtarı: {} karakter; {} byte ham metin, {} karakter hex veya {} byte'a çözülen base64 olmalı",
+            value.chars().count(),
+            AES_256_KEY_LEN,
+            AES_256_KEY_LEN * 2,
+            AES_256_KEY_LEN
+        ))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    pub fn encrypt<T: Serialize>(&self, data: &T) -> Result<String> {
+        encrypt_payload(data, self.cipher.as_ref())
+    }
+
+    pub fn decrypt<T: DeserializeOwned>(&self, encoded: &str) -> Result<T> {
+        decrypt_payload(encoded, self.cipher.as_ref())
+    }
+}
+
+impl PayloadCipher for MigrosKey {
+    fn encrypt_bytes(&self, plain: &[u8]) -> Result<Vec<u8>> {
+        self.cipher.encrypt_bytes(plain)
+    }
+
+    fn decrypt_bytes(&self, cipher_text: &[u8]) -> Result<Vec<u8>> {
+        self.cipher.decrypt_bytes(cipher_text)
+    }
+}
+
+impl std::fmt::Debug for MigrosKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Anahtarın tamamı loglara yazılmaz
+        let prefix: String = self.value.chars().take(4).collect();
+        write!(f, "MigrosKey({}…)", prefix)
+    }
+}
+
+pub fn encrypt_migros<T: Serialize>(data: &T, secret_key: &str) -> Result<String> {
+
+    // 1. Anahtarı doğrula ve şifreleyici oluştur
+    let key = MigrosKey::parse(secret_key)?;
+
+    // 2. JSON → şifreleme → base64
+    key.encrypt(data)
+}
+
+/// `encrypt_migros` ile şifrelenmiş (ör. yakalanmış bir istekteki) payload'ı çözer.
+pub fn decrypt_migros<T: DeserializeOwned>(encoded: &str, secret_key: &str) -> Result<T> {
+    MigrosKey::parse(secret_key)?.decrypt(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    const KEY: [u8; AES_256_KEY_LEN] = *b"0123456789abcdef0123456789abcdef";
+    const OTHER_KEY: [u8; AES_256_KEY_LEN] = *b"fedcba9876543210fedcba9876543210";
+
+    fn payload() -> Value {
+        json!({ "storeId": 100, "productId": 42, "status": "PASSIVE" })
+    }
+
+    fn assert_round_trip(cipher: &dyn PayloadCipher) {
+        let encoded = encrypt_payload(&payload(), cipher).unwrap();
+        let decoded: Value = decrypt_payload(&encoded, cipher).unwrap();
+        assert_eq!(decoded, payload());
+    }
+
+    #[test]
+    fn ecb_round_trip() {
+        assert_round_trip(&Aes256EcbCipher::new(&KEY).unwrap());
+    }
+
+    #[test]
+    fn cbc_fixed_iv_round_trip_is_deterministic() {
+        let cipher = Aes256CbcCipher::new(&KEY, Some([7u8; AES_BLOCK_LEN])).unwrap();
+        assert_round_trip(&cipher);
+        assert_eq!(
+            encrypt_payload(&payload(), &cipher).unwrap(),
+            encrypt_payload(&payload(), &cipher).unwrap()
+        );
+    }
+
+    #[test]
+    fn cbc_random_iv_round_trip_differs_per_call() {
+        let cipher = Aes256CbcCipher::new(&KEY, None).unwrap();
+        assert_round_trip(&cipher);
+        assert_ne!(
+            encrypt_payload(&payload(), &cipher).unwrap(),
+            encrypt_payload(&payload(), &cipher).unwrap()
+        );
+    }
+
+    #[test]
+    fn gcm_round_trip() {
+        assert_round_trip(&Aes256GcmCipher::new(&KEY).unwrap());
+    }
+
+    #[test]
+    fn gcm_rejects_tampered_cipher_text() {
+        let cipher = Aes256GcmCipher::new(&KEY).unwrap();
+        let mut cipher_text = cipher.encrypt_bytes(b"{\"status\":\"ACTIVE\"}").unwrap();
+        let last = cipher_text.len() - 1;
+        cipher_text[last] ^= 0x01;
+
+        assert!(cipher.decrypt_bytes(&cipher_text).is_err());
+    }
+
+    #[test]
+    fn gcm_rejects_wrong_key() {
+        let encoded = encrypt_payload(&payload(), &Aes256GcmCipher::new(&KEY).unwrap()).unwrap();
+        let other = Aes256GcmCipher::new(&OTHER_KEY).unwrap();
+
+        assert!(decrypt_payload::<Value>(&encoded, &other).is_err());
+    }
+
+    #[test]
+    fn migros_key_uses_factory_scheme() {
+        let raw = std::str::from_utf8(&KEY).unwrap();
+        let gcm = MigrosKey::parse_with(raw, &PayloadCipherFactory::aes256_gcm()).unwrap();
+        let encoded = gcm.encrypt(&payload()).unwrap();
+
+        assert_eq!(gcm.decrypt::<Value>(&encoded).unwrap(), payload());
+        assert!(
+            MigrosKey::parse(raw)
+                .unwrap()
+                .decrypt::<Value>(&encoded)
+                .is_err()
+        );
+    }
+
+    fn raw_key() -> &'static str {
+        std::str::from_utf8(&KEY).unwrap()
+    }
+
+    /// ECB deterministik olduğundan aynı anahtar aynı şifreli metni üretir.
+    fn assert_same_cipher(a: &MigrosKey, b: &MigrosKey) {
+        assert_eq!(
+            a.encrypt(&payload()).unwrap(),
+            b.encrypt(&payload()).unwrap()
+        );
+    }
+
+    #[test]
+    fn migros_key_rejects_wrong_length() {
+        for len in [AES_256_KEY_LEN - 1, AES_256_KEY_LEN + 1] {
+            let err = MigrosKey::parse(&"a".repeat(len)).unwrap_err().to_string();
+            assert!(
+                err.contains(&format!("Geçersiz Migros anahtarı: {} karakter", len)),
+                "beklenmeyen hata: {}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn migros_key_decodes_hex() {
+        let hex: String = KEY.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex.len(), AES_256_KEY_LEN * 2);
+
+        assert_same_cipher(
+            &MigrosKey::parse(&hex).unwrap(),
+            &MigrosKey::parse(raw_key()).unwrap(),
+        );
+    }
+
+    #[test]
+    fn migros_key_decodes_base64_to_raw_cipher() {
+        let encoded = general_purpose::STANDARD.encode(KEY);
+
+        assert_same_cipher(
+            &MigrosKey::parse(&encoded).unwrap(),
+            &MigrosKey::parse(raw_key()).unwrap(),
+        );
+    }
+
+    #[test]
+    fn migros_key_trims_whitespace() {
+        let key = MigrosKey::parse(&format!("  {}\n", raw_key())).unwrap();
+
+        assert_eq!(key.as_str(), raw_key());
+        assert_same_cipher(&key, &MigrosKey::parse(raw_key()).unwrap());
+    }
+}
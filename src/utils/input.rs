@@ -11,10 +11,7 @@ pub fn find_value_by_key_in_json(path: &str, target_key: &str) -> Option<String>
     let json: Value = serde_json::from_str(&contents).ok()?;
 
     // Sadece string olan değerleri döndür
-    match json.get(target_key)?.as_str() {
-        Some(s) => Some(s.to_string()),
-        None => None,
-    }
+    json.get(target_key)?.as_str().map(|s| s.to_string())
 }
 
 pub fn string_to_i64<'de, D>(deserializer: D) -> Result<i64, D::Error>
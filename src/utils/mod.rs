@@ -1,4 +1,6 @@
+pub mod concurrency;
 pub mod encrypt;
 pub mod input;
 pub mod http;
-pub mod logger;
\ No newline at end of file
+pub mod logger;
+pub mod text;
\ No newline at end of file
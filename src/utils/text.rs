@@ -0,0 +1,70 @@
+/// Türkçe kurallarıyla küçük harfe çevirir: "I" → "ı", "İ" → "i".
+pub fn turkish_lowercase(value: &str) -> String {
+    value
+        .chars()
+        .flat_map(|c| match c {
+            'I' => vec!['ı'],
+            'İ' => vec!['i'],
+            _ => c.to_lowercase().collect(),
+        })
+        // "İ".to_lowercase() bazı ortamlarda "i̇" (i + U+0307) üretir
+        .filter(|c| *c != '\u{0307}')
+        .collect()
+}
+
+/// Baştaki/sondaki boşlukları atar, aradaki boşlukları tek boşluğa indirir.
+pub fn normalize_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Karşılaştırma anahtarı: Türkçe küçük harf + boşluk normalizasyonu; "ı" ve "i" aynı harf
+/// sayılır. "  KADIKÖY " ve "Kadıköy", "BURGER KING" ve "Burger King" aynı sonucu verir.
+pub fn turkish_fold(value: &str) -> String {
+    let folded: String = turkish_lowercase(value)
+        .chars()
+        .map(|c| if c == 'ı' { 'i' } else { c })
+        .collect();
+
+    normalize_whitespace(&folded)
+}
+
+/// Ürün adı eşleştirmesi için daha gevşek anahtar: Türkçe karakterleri ASCII karşılığına
+/// indirger ("çiğ köfte" → "cig kofte"), noktalama işaretlerini boşluğa çevirir.
+pub fn normalize_turkish_name(value: &str) -> String {
+    let folded: String = turkish_lowercase(value)
+        .chars()
+        .map(|c| match c {
+            'ç' => 'c',
+            'ğ' => 'g',
+            'ı' => 'i',
+            'ö' => 'o',
+            'ş' => 's',
+            'ü' => 'u',
+            'â' => 'a',
+            'î' => 'i',
+            'û' => 'u',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect();
+
+    normalize_whitespace(&folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_matches_turkish_case_and_whitespace_variants() {
+        assert_eq!(turkish_fold("KADIKÖY"), turkish_fold("Kadıköy"));
+        assert_eq!(turkish_fold(" kadıköy "), turkish_fold("Kadıköy"));
+        assert_eq!(turkish_fold("İSTANBUL"), turkish_fold("istanbul"));
+    }
+
+    #[test]
+    fn fold_matches_ascii_brand_names() {
+        assert_eq!(turkish_fold("BURGER KING"), turkish_fold("Burger King"));
+        assert_eq!(turkish_fold("  BURGER   KING "), "burger king");
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use avane_ck_rust_lib::schemas::struct_enums::{RoofStep, RoofStepResult};
+use avane_ck_rust_lib::schemas::struct_migros::{MigrosBranchJsonRow, MigrosDesiredState};
+use avane_ck_rust_lib::services::migros::{MIGROS_PRODUCT_STATUS_PATH, MigrosClient};
+use avane_ck_rust_lib::services::store_filter::StoreFilter;
+use avane_ck_rust_lib::services::migros_flagger::MigrosFlagger;
+use avane_ck_rust_lib::services::migros_mock::{
+    MigrosMockFailure, MigrosMockFailureRule, MigrosMockServer, MigrosMockStore,
+};
+use avane_ck_rust_lib::services::migros_pipeline::RoofPipeline;
+
+const STORE_ID: i64 = 100;
+const RESTAURANT_KEY: &str = "0123456789abcdef0123456789abcdef";
+
+fn branch_row(r#type: &str, product_id: i64, urun_id: i64) -> MigrosBranchJsonRow {
+    MigrosBranchJsonRow {
+        restaurant_key: RESTAURANT_KEY.to_string(),
+        integration_name: "migros".to_string(),
+        r#type: r#type.to_string(),
+        restaurant_id: STORE_ID,
+        product_id,
+        product_name: format!("Ürün {}", product_id),
+        category_name: "Kategori".to_string(),
+        price: 100.0,
+        description: None,
+        urun_id: Some(urun_id),
+    }
+}
+
+fn step_results(results: &[RoofStepResult], step: RoofStep) -> Vec<&RoofStepResult> {
+    results.iter().filter(|result| result.step == step).collect()
+}
+
+#[tokio::test]
+async fn roof_pipeline_flags_retries_and_verifies_against_mock() {
+    let server = MigrosMockServer::start().await.unwrap();
+    let mock_store = MigrosMockStore::new(1, STORE_ID, 500, RESTAURANT_KEY)
+        .with_product(10, 1, true, 120.0)
+        .with_product(10, 2, true, 80.0)
+        .with_product(20, 3, false, 60.0)
+        .with_option(1, 70, 77, true);
+    let stores = vec![mock_store.key_row("Kadıköy")];
+    server.add_store(mock_store).await;
+
+    // İlk durum isteği sistem hatası alır; istemci aynı isteği tekrar denemeli
+    server
+        .inject_failure(MigrosMockFailureRule {
+            path: MIGROS_PRODUCT_STATUS_PATH.to_string(),
+            store_id: Some(STORE_ID),
+            failure: MigrosMockFailure::error("SYSTEM_ERROR", true),
+            times: 1,
+        })
+        .await;
+
+    // urun 10 tükendi (ürün 1 ve opsiyon 77), urun 30 stokta (ürün 3); ürün 2 eşleşmesiz
+    let branch_rows = vec![
+        branch_row("product", 1, 10),
+        branch_row("option", 77, 10),
+        branch_row("product", 3, 30),
+    ];
+    let desired = MigrosDesiredState {
+        urun_status: HashMap::from([(10, false), (30, true)]),
+        ..Default::default()
+    };
+    let flagger = MigrosFlagger::new(&branch_rows, &desired);
+
+    let client = MigrosClient::new()
+        .with_base_url(&server.base_url())
+        .with_max_retries(2);
+    let results = RoofPipeline::new(client)
+        .with_verification(Duration::from_millis(10))
+        .run(&stores, &StoreFilter::new(), |store, store_menu| {
+            Ok(flagger.diff(store, store_menu))
+        })
+        .await;
+
+    for result in &results {
+        assert!(result.result.is_ok(), "{:?} başarısız: {:?}", result.step, result.result);
+    }
+    assert_eq!(step_results(&results, RoofStep::Flagger)[0].item_count, 3);
+    assert_eq!(step_results(&results, RoofStep::IstekAt)[0].item_count, 3);
+    assert_eq!(step_results(&results, RoofStep::Dogrula).len(), 1);
+
+    assert_eq!(server.product_status(STORE_ID, 1).await, Some(false));
+    assert_eq!(server.product_status(STORE_ID, 2).await, Some(true));
+    assert_eq!(server.product_status(STORE_ID, 3).await, Some(true));
+    assert_eq!(server.option_status(STORE_ID, 77).await, Some(false));
+
+    // 2 ürün isteği + sistem hatası sonrası 1 tekrar
+    let product_requests = server
+        .requests()
+        .await
+        .into_iter()
+        .filter(|request| request.path.ends_with(MIGROS_PRODUCT_STATUS_PATH))
+        .count();
+    assert_eq!(product_requests, 3);
+}